@@ -21,7 +21,8 @@ fn bench_supercluster(c: &mut Criterion) {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
 
     let mut cluster = Supercluster::new(options);
 
@@ -31,7 +32,7 @@ fn bench_supercluster(c: &mut Criterion) {
         })
     });
 
-    let index = cluster.load(feature_collection).unwrap();
+    let (index, _) = cluster.load(feature_collection).unwrap();
 
     c.bench_function("get tile", |b| {
         b.iter(|| {
@@ -44,6 +45,12 @@ fn bench_supercluster(c: &mut Criterion) {
             let _ = index.get_clusters(black_box([101.0, 0.0, 105.0, 2.0]), black_box(2));
         })
     });
+
+    c.bench_function("get cluster items ref", |b| {
+        b.iter(|| {
+            let _ = index.get_cluster_items_ref(black_box([101.0, 0.0, 105.0, 2.0]), black_box(2));
+        })
+    });
 }
 
 criterion_group!(benches, bench_supercluster);
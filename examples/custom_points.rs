@@ -0,0 +1,87 @@
+use supercluster::{Cluster, ClusterPoint, CoordinateSystem, Supercluster, SuperclusterError};
+
+/// A charging station clustered without converting it into a GeoJSON feature.
+#[derive(Clone, Debug)]
+struct Station {
+    name: String,
+    lng: f64,
+    lat: f64,
+    connectors: u32,
+}
+
+/// A station or a cluster of stations returned from the queries.
+#[derive(Debug)]
+enum Item {
+    Station(Station),
+    Cluster(Cluster),
+}
+
+impl ClusterPoint for Station {
+    type Payload = Item;
+
+    fn coords(&self) -> Option<[f64; 2]> {
+        Some([self.lng, self.lat])
+    }
+
+    fn payload(&self) -> Item {
+        Item::Station(self.clone())
+    }
+
+    fn cluster_payload(cluster: Cluster) -> Item {
+        Item::Cluster(cluster)
+    }
+
+    fn weight(&self) -> f64 {
+        self.connectors as f64
+    }
+}
+
+fn main() -> Result<(), SuperclusterError> {
+    // Create a few stations
+    let stations = vec![
+        Station {
+            name: "Station 1".to_string(),
+            lng: 4.89,
+            lat: 52.37,
+            connectors: 2,
+        },
+        Station {
+            name: "Station 2".to_string(),
+            lng: 4.90,
+            lat: 52.37,
+            connectors: 4,
+        },
+        Station {
+            name: "Station 3".to_string(),
+            lng: 13.40,
+            lat: 52.52,
+            connectors: 1,
+        },
+    ];
+
+    // Set the configuration settings
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::LatLng)
+        .build()?;
+
+    // Create a new instance clustering the stations
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(stations)?;
+
+    // Get the typed clusters and stations of the world
+    for item in index.get_clusters([-180.0, -90.0, 180.0, 90.0], 3)? {
+        match item {
+            Item::Station(station) => println!("Station: {}", station.name),
+            Item::Cluster(cluster) => println!(
+                "Cluster {} of {} stations with {} connectors",
+                cluster.id, cluster.count, cluster.weight
+            ),
+        }
+    }
+
+    Ok(())
+}
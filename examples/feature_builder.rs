@@ -15,13 +15,13 @@ fn main() -> Result<(), SuperclusterError> {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()?;
 
     // Create a new instance with the specified configuration settings
     let mut cluster = Supercluster::new(options);
 
     // Load the FeatureCollection into the Supercluster instance
-    let index = cluster.load(features)?;
+    let (index, _) = cluster.load(features)?;
 
     // Get a tile from the Supercluster instance
     let tile = index.get_tile(0, 0.0, 0.0)?;
@@ -26,7 +26,7 @@ fn main() -> Result<(), SuperclusterError> {
     let mut cluster = Supercluster::new(options);
 
     // Load features into the Supercluster instance
-    let index = cluster.load(features)?;
+    let (index, _) = cluster.load(features)?;
 
     // Get a tile from the Supercluster instance
     let tile = index.get_tile(0, 0.0, 0.0)?;
@@ -2,14 +2,161 @@
 //!
 //! This module contains the builder pattern for the supercluster configuration settings.
 
-use std::{collections::HashMap, hash::BuildHasherDefault};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::BuildHasherDefault,
+    sync::Arc,
+};
 
+#[cfg(feature = "cluster_metadata")]
+use geojson::JsonObject;
 use geojson::{feature::Id, Feature, Geometry, Value};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use twox_hash::XxHash64;
 
-use crate::CoordinateSystem;
+use crate::{
+    Callback, ClusterAlgorithm, ClusterIdMode, ClusterPosition, CoordinateSystem, GeometryMode,
+    Projection, RadiusUnit, SuperclusterError, ValidationPolicy,
+};
+
+/// Function mapping the properties of an input point to the properties aggregated into clusters.
+#[cfg(feature = "cluster_metadata")]
+pub type MapFn = dyn Fn(&JsonObject) -> JsonObject + Send + Sync;
+
+/// Function merging the mapped properties of a point or a child cluster into the accumulated cluster properties.
+#[cfg(feature = "cluster_metadata")]
+pub type ReduceFn = dyn Fn(&mut JsonObject, &JsonObject) + Send + Sync;
+
+/// Function computing the weight of an input point.
+pub type WeightFn = dyn Fn(&Feature) -> f64 + Send + Sync;
+
+/// Weight of an input point, used for the cluster threshold and the cluster position.
+/// Points without a valid weight count as 1.0, negative weights count as 0.0.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum PointWeight {
+    /// Read the weight from a numeric property of the feature.
+    Property(String),
+
+    /// Compute the weight with a function over the feature.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Callback<WeightFn>),
+}
+
+impl PointWeight {
+    /// Compute the weight of an input point.
+    ///
+    /// # Arguments
+    ///
+    /// - `feature`: The GeoJSON feature representing the point.
+    ///
+    /// # Returns
+    ///
+    /// The weight of the point.
+    pub fn weight(&self, feature: &Feature) -> f64 {
+        let weight = match self {
+            PointWeight::Property(key) => feature.property(key).and_then(|value| value.as_f64()),
+            PointWeight::Custom(weight) => Some(weight(feature)),
+        };
+
+        match weight {
+            Some(weight) if weight.is_finite() => weight.max(0.0),
+            _ => 1.0,
+        }
+    }
+}
+
+/// Function computing the partition key of an input point.
+pub type PartitionFn = dyn Fn(&Feature) -> String + Send + Sync;
+
+/// Key splitting the input points into partitions that are clustered separately.
+/// Points are only merged with points of the same partition.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum PartitionKey {
+    /// Read the key from a property of the feature.
+    /// String values are used as is, other values are serialized to JSON.
+    Property(String),
+
+    /// Compute the key with a function over the feature.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Callback<PartitionFn>),
+}
+
+impl PartitionKey {
+    /// Compute the partition key of an input point.
+    ///
+    /// # Arguments
+    ///
+    /// - `feature`: The GeoJSON feature representing the point.
+    ///
+    /// # Returns
+    ///
+    /// The partition key of the point, otherwise `None` if the feature has no such property.
+    pub fn key(&self, feature: &Feature) -> Option<String> {
+        match self {
+            PartitionKey::Property(key) => {
+                feature.property(key).map(|value| match value.as_str() {
+                    Some(value) => value.to_string(),
+                    None => value.to_string(),
+                })
+            }
+            PartitionKey::Custom(key) => Some(key(feature)),
+        }
+    }
+}
+
+/// Clustering parameters overriding `radius` and `min_points` at a zoom level.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ZoomLevel {
+    /// Cluster radius at the zoom level.
+    /// The default value is `None`, which keeps the `radius` option.
+    pub radius: Option<f64>,
+
+    /// Minimum points to form a cluster at the zoom level.
+    /// The default value is `None`, which keeps the `min_points` option.
+    pub min_points: Option<u8>,
+}
+
+/// Function returning the clustering parameters of a zoom level.
+pub type ZoomScheduleFn = dyn Fn(u8) -> ZoomLevel + Send + Sync;
+
+/// Schedule of the clustering parameters per zoom level.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ZoomSchedule {
+    /// Table of clustering parameters by zoom level.
+    /// An entry applies from its zoom level up to the next entry of the table.
+    Table(BTreeMap<u8, ZoomLevel>),
+
+    /// Compute the clustering parameters of a zoom level with a function.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Callback<ZoomScheduleFn>),
+}
+
+impl ZoomSchedule {
+    /// Get the clustering parameters of a zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `zoom`: The zoom level.
+    ///
+    /// # Returns
+    ///
+    /// The clustering parameters overriding the options at the zoom level.
+    pub fn level(&self, zoom: u8) -> ZoomLevel {
+        match self {
+            ZoomSchedule::Table(table) => table
+                .range(..=zoom)
+                .next_back()
+                .map(|(_, level)| level.to_owned())
+                .unwrap_or_default(),
+            ZoomSchedule::Custom(schedule) => schedule(zoom),
+        }
+    }
+}
 
 /// Supercluster configuration options.
 #[derive(Clone, Debug)]
@@ -24,13 +171,18 @@ pub struct SuperclusterOptions {
     pub max_zoom: u8,
 
     /// Minimum points to form a cluster.
+    /// If `weight` is set, the total weight of the points is compared instead.
     /// The default value is 2.
     pub min_points: u8,
 
-    /// Cluster radius, in pixels.
+    /// Cluster radius, in pixels, or in meters with `RadiusUnit::Meters`.
     /// The default value is 40.0.
     pub radius: f64,
 
+    /// Unit of the cluster radius, including the radius of the zoom schedule.
+    /// The default value is `RadiusUnit::Pixels`.
+    pub radius_unit: RadiusUnit,
+
     /// Tile extent (radius is calculated relative to it).
     /// The default value is 512.0.
     pub extent: f64,
@@ -42,6 +194,177 @@ pub struct SuperclusterOptions {
     /// Type of coordinate system for clustering.
     /// The default value is `CoordinateSystem::LatLng`.
     pub coordinate_system: CoordinateSystem,
+
+    /// Whether to cluster the points across the wrapping edges of the coordinate system,
+    /// e.g. across the antimeridian with `CoordinateSystem::LatLng`. Cartesian coordinates never wrap.
+    /// The default value is `false`.
+    pub wrap_clusters: bool,
+
+    /// Clustering algorithm applied at each zoom level.
+    /// The default value is `ClusterAlgorithm::Radius`.
+    pub algorithm: ClusterAlgorithm,
+
+    /// Position at which the clusters are displayed.
+    /// The default value is `ClusterPosition::WeightedMean`.
+    pub position: ClusterPosition,
+
+    /// Whether to include the bounding box of the clustered points in the cluster features.
+    /// The default value is `false`.
+    pub cluster_bbox: bool,
+
+    /// Identity of the clusters in the output features.
+    /// The default value is `ClusterIdMode::Index`.
+    pub id_mode: ClusterIdMode,
+
+    /// Handling of the input features whose geometry is not a point.
+    /// The default value is `GeometryMode::PointsOnly`.
+    pub geometry_mode: GeometryMode,
+
+    /// Handling of the input points without geometry or with invalid coordinates.
+    /// The default value is `ValidationPolicy::Skip`.
+    pub validation: ValidationPolicy,
+
+    /// Schedule of the cluster radius and minimum points per zoom level.
+    /// The default value is `None`, which applies `radius` and `min_points` at every zoom level.
+    pub zoom_schedule: Option<ZoomSchedule>,
+
+    /// Key of the partitions clustered separately, e.g. to never merge fast and slow chargers.
+    /// The default value is `None`, which clusters all the points together.
+    pub partition: Option<PartitionKey>,
+
+    /// Weight of the input points.
+    /// The total weight of a cluster is compared with `min_points` and pulls the cluster position towards heavier points.
+    /// The default value is `None`, which gives every point a weight of 1.0.
+    pub weight: Option<PointWeight>,
+
+    /// Function mapping the point properties to the properties aggregated into clusters.
+    /// Only used together with `reduce`. The default value is `None`, which keeps the point properties as is.
+    #[cfg(feature = "cluster_metadata")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub map: Option<Callback<MapFn>>,
+
+    /// Function aggregating the mapped properties into the cluster properties.
+    /// The default value is `None`, which disables the property aggregation.
+    #[cfg(feature = "cluster_metadata")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub reduce: Option<Callback<ReduceFn>>,
+}
+
+impl SuperclusterOptions {
+    /// Get the cluster radius at a zoom level, taking the zoom schedule into account.
+    ///
+    /// # Arguments
+    ///
+    /// - `zoom`: The zoom level.
+    ///
+    /// # Returns
+    ///
+    /// The cluster radius at the zoom level.
+    pub fn radius_at(&self, zoom: usize) -> f64 {
+        self.zoom_level(zoom).radius.unwrap_or(self.radius)
+    }
+
+    /// Get the minimum points to form a cluster at a zoom level, taking the zoom schedule into account.
+    ///
+    /// # Arguments
+    ///
+    /// - `zoom`: The zoom level.
+    ///
+    /// # Returns
+    ///
+    /// The minimum points to form a cluster at the zoom level.
+    pub fn min_points_at(&self, zoom: usize) -> u8 {
+        self.zoom_level(zoom).min_points.unwrap_or(self.min_points)
+    }
+
+    /// Check that the options describe a valid clustering.
+    ///
+    /// # Returns
+    ///
+    /// Nothing if the options are valid, otherwise an error describing the first invalid option.
+    pub fn validate(&self) -> Result<(), SuperclusterError> {
+        let invalid = |message: String| Err(SuperclusterError::InvalidOptions(message));
+
+        if self.min_zoom > self.max_zoom {
+            return invalid(format!(
+                "min_zoom {} is greater than max_zoom {}",
+                self.min_zoom, self.max_zoom
+            ));
+        }
+
+        // The index keeps one extra tree above the maximal zoom level
+        if self.max_zoom == u8::MAX {
+            return invalid(format!("max_zoom must be lower than {}", u8::MAX));
+        }
+
+        if !self.radius.is_finite() || self.radius < 0.0 {
+            return invalid(format!(
+                "radius {} must be a finite non-negative number",
+                self.radius
+            ));
+        }
+
+        // The schedule may override the radius at every zoom level the points are clustered on
+        for zoom in self.min_zoom..=self.max_zoom {
+            let radius = self.radius_at(zoom as usize);
+
+            if !radius.is_finite() || radius < 0.0 {
+                return invalid(format!(
+                    "radius {} at zoom level {} must be a finite non-negative number",
+                    radius, zoom
+                ));
+            }
+
+            // A grid cell as wide as the radius must have a positive size
+            if radius == 0.0 && matches!(self.algorithm, ClusterAlgorithm::Grid { .. }) {
+                return invalid(format!(
+                    "radius at zoom level {} must be positive with the grid algorithm",
+                    zoom
+                ));
+            }
+        }
+
+        if self.radius_unit == RadiusUnit::Meters {
+            if self.coordinate_system != CoordinateSystem::LatLng {
+                return invalid(
+                    "radius in meters requires the LatLng coordinate system".to_string(),
+                );
+            }
+
+            if self.algorithm != ClusterAlgorithm::Radius {
+                return invalid("radius in meters requires the radius algorithm".to_string());
+            }
+        }
+
+        if !self.extent.is_finite() || self.extent <= 0.0 {
+            return invalid(format!(
+                "extent {} must be a finite positive number",
+                self.extent
+            ));
+        }
+
+        if self.node_size == 0 {
+            return invalid("node_size must be positive".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Get the scheduled clustering parameters of a zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `zoom`: The zoom level.
+    ///
+    /// # Returns
+    ///
+    /// The scheduled clustering parameters, otherwise empty parameters if there is no schedule.
+    fn zoom_level(&self, zoom: usize) -> ZoomLevel {
+        match &self.zoom_schedule {
+            Some(schedule) => schedule.level(zoom.min(u8::MAX as usize) as u8),
+            None => ZoomLevel::default(),
+        }
+    }
 }
 
 /// Feature configuration options builder.
@@ -129,10 +452,14 @@ pub struct SuperclusterBuilder {
     /// The default value is 2.
     pub min_points: Option<u8>,
 
-    /// Cluster radius, in pixels.
+    /// Cluster radius, in pixels, or in meters with `RadiusUnit::Meters`.
     /// The default value is 40.0.
     pub radius: Option<f64>,
 
+    /// Unit of the cluster radius.
+    /// The default value is `RadiusUnit::Pixels`.
+    pub radius_unit: Option<RadiusUnit>,
+
     /// Tile extent (radius is calculated relative to it).
     /// The default value is 512.0.
     pub extent: Option<f64>,
@@ -144,6 +471,58 @@ pub struct SuperclusterBuilder {
     /// Type of coordinate system for clustering.
     /// The default value is `CoordinateSystem::LatLng`.
     pub coordinate_system: Option<CoordinateSystem>,
+
+    /// Whether to cluster the points across the wrapping edges of the coordinate system.
+    /// The default value is `false`.
+    pub wrap_clusters: Option<bool>,
+
+    /// Clustering algorithm applied at each zoom level.
+    /// The default value is `ClusterAlgorithm::Radius`.
+    pub algorithm: Option<ClusterAlgorithm>,
+
+    /// Position at which the clusters are displayed.
+    /// The default value is `ClusterPosition::WeightedMean`.
+    pub position: Option<ClusterPosition>,
+
+    /// Whether to include the bounding box of the clustered points in the cluster features.
+    /// The default value is `false`.
+    pub cluster_bbox: Option<bool>,
+
+    /// Identity of the clusters in the output features.
+    /// The default value is `ClusterIdMode::Index`.
+    pub id_mode: Option<ClusterIdMode>,
+
+    /// Handling of the input features whose geometry is not a point.
+    /// The default value is `GeometryMode::PointsOnly`.
+    pub geometry_mode: Option<GeometryMode>,
+
+    /// Handling of the input points without geometry or with invalid coordinates.
+    /// The default value is `ValidationPolicy::Skip`.
+    pub validation: Option<ValidationPolicy>,
+
+    /// Schedule of the cluster radius and minimum points per zoom level.
+    /// The default value is `None`.
+    pub zoom_schedule: Option<ZoomSchedule>,
+
+    /// Key of the partitions clustered separately.
+    /// The default value is `None`.
+    pub partition: Option<PartitionKey>,
+
+    /// Weight of the input points.
+    /// The default value is `None`.
+    pub weight: Option<PointWeight>,
+
+    /// Function mapping the point properties to the properties aggregated into clusters.
+    /// The default value is `None`.
+    #[cfg(feature = "cluster_metadata")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub map: Option<Callback<MapFn>>,
+
+    /// Function aggregating the mapped properties into the cluster properties.
+    /// The default value is `None`.
+    #[cfg(feature = "cluster_metadata")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub reduce: Option<Callback<ReduceFn>>,
 }
 
 impl SuperclusterBuilder {
@@ -212,6 +591,20 @@ impl SuperclusterBuilder {
         self
     }
 
+    /// Set the unit of the cluster radius, e.g. meters to cluster the same ground distance at every latitude.
+    ///
+    /// # Arguments
+    ///
+    /// - `radius_unit`: Unit of the cluster radius, including the radius of the zoom schedule.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn radius_unit(mut self, radius_unit: RadiusUnit) -> Self {
+        self.radius_unit = Some(radius_unit);
+        self
+    }
+
     /// Set the tile extent (radius is calculated relative to it).
     ///
     /// # Arguments
@@ -254,21 +647,298 @@ impl SuperclusterBuilder {
         self
     }
 
+    /// Set the projection of the input coordinates into the clustering space, using the custom coordinate system.
+    ///
+    /// # Arguments
+    ///
+    /// - `projection`: Projection of the input coordinates into the unit square.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn projection<P>(mut self, projection: P) -> Self
+    where
+        P: Projection + 'static,
+    {
+        self.coordinate_system = Some(CoordinateSystem::Custom(Callback::new(Arc::new(
+            projection,
+        ))));
+        self
+    }
+
+    /// Set whether to cluster the points across the wrapping edges of the coordinate system, e.g. across the antimeridian.
+    ///
+    /// # Arguments
+    ///
+    /// - `wrap_clusters`: Whether to cluster the points across the wrapping edges.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn wrap_clusters(mut self, wrap_clusters: bool) -> Self {
+        self.wrap_clusters = Some(wrap_clusters);
+        self
+    }
+
+    /// Set the clustering algorithm applied at each zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `algorithm`: Clustering algorithm applied at each zoom level.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn algorithm(mut self, algorithm: ClusterAlgorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Set the position at which the clusters are displayed.
+    ///
+    /// # Arguments
+    ///
+    /// - `position`: Position at which the clusters are displayed.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn position(mut self, position: ClusterPosition) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Set whether to include the bounding box of the clustered points in the cluster features.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_bbox`: Whether to include the bounding box of the clusters.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn cluster_bbox(mut self, cluster_bbox: bool) -> Self {
+        self.cluster_bbox = Some(cluster_bbox);
+        self
+    }
+
+    /// Set the identity of the clusters in the output features.
+    ///
+    /// # Arguments
+    ///
+    /// - `id_mode`: Identity of the clusters in the output features.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn id_mode(mut self, id_mode: ClusterIdMode) -> Self {
+        self.id_mode = Some(id_mode);
+        self
+    }
+
+    /// Set the handling of the input features whose geometry is not a point.
+    ///
+    /// # Arguments
+    ///
+    /// - `geometry_mode`: Handling of the input features whose geometry is not a point.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn geometry_mode(mut self, geometry_mode: GeometryMode) -> Self {
+        self.geometry_mode = Some(geometry_mode);
+        self
+    }
+
+    /// Set the handling of the input points without geometry or with invalid coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// - `validation`: Handling of the invalid input points.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn validation(mut self, validation: ValidationPolicy) -> Self {
+        self.validation = Some(validation);
+        self
+    }
+
+    /// Set the cluster radius and minimum points from a zoom level up to the next scheduled zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `zoom`: The first zoom level the parameters apply to.
+    /// - `level`: The clustering parameters overriding `radius` and `min_points`.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn zoom_level(mut self, zoom: u8, level: ZoomLevel) -> Self {
+        let mut table = match self.zoom_schedule {
+            Some(ZoomSchedule::Table(table)) => table,
+            _ => BTreeMap::new(),
+        };
+
+        table.insert(zoom, level);
+
+        self.zoom_schedule = Some(ZoomSchedule::Table(table));
+        self
+    }
+
+    /// Set the function returning the cluster radius and minimum points of a zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `schedule`: Function returning the clustering parameters of a zoom level.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn zoom_schedule_fn<F>(mut self, schedule: F) -> Self
+    where
+        F: Fn(u8) -> ZoomLevel + Send + Sync + 'static,
+    {
+        self.zoom_schedule = Some(ZoomSchedule::Custom(Callback::new(Arc::new(schedule))));
+        self
+    }
+
+    /// Set the numeric property holding the weight of the input points.
+    ///
+    /// # Arguments
+    ///
+    /// - `key`: The name of the weight property.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn weight_property(mut self, key: &str) -> Self {
+        self.weight = Some(PointWeight::Property(key.to_string()));
+        self
+    }
+
+    /// Set the function computing the weight of the input points.
+    ///
+    /// # Arguments
+    ///
+    /// - `weight`: Function computing the weight of a feature.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn weight_fn<F>(mut self, weight: F) -> Self
+    where
+        F: Fn(&Feature) -> f64 + Send + Sync + 'static,
+    {
+        self.weight = Some(PointWeight::Custom(Callback::new(Arc::new(weight))));
+        self
+    }
+
+    /// Set the property holding the partition key of the input points.
+    /// Only points with the same key are merged into a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `key`: The name of the partition property.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn partition_property(mut self, key: &str) -> Self {
+        self.partition = Some(PartitionKey::Property(key.to_string()));
+        self
+    }
+
+    /// Set the function computing the partition key of the input points.
+    /// Only points with the same key are merged into a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `partition`: Function computing the partition key of a feature.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    pub fn partition_fn<F>(mut self, partition: F) -> Self
+    where
+        F: Fn(&Feature) -> String + Send + Sync + 'static,
+    {
+        self.partition = Some(PartitionKey::Custom(Callback::new(Arc::new(partition))));
+        self
+    }
+
+    /// Set the function mapping the point properties to the properties aggregated into clusters.
+    /// The mapped properties of every point are passed to the `reduce` function.
+    ///
+    /// # Arguments
+    ///
+    /// - `map`: Function mapping the point properties.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    #[cfg(feature = "cluster_metadata")]
+    pub fn map<F>(mut self, map: F) -> Self
+    where
+        F: Fn(&JsonObject) -> JsonObject + Send + Sync + 'static,
+    {
+        self.map = Some(Callback::new(Arc::new(map)));
+        self
+    }
+
+    /// Set the function aggregating the mapped properties into the cluster properties.
+    /// The first argument is the accumulated cluster properties, the second one the properties of a merged point or cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `reduce`: Function aggregating the cluster properties.
+    ///
+    /// # Returns
+    ///
+    /// The supercluster options builder.
+    #[cfg(feature = "cluster_metadata")]
+    pub fn reduce<F>(mut self, reduce: F) -> Self
+    where
+        F: Fn(&mut JsonObject, &JsonObject) + Send + Sync + 'static,
+    {
+        self.reduce = Some(Callback::new(Arc::new(reduce)));
+        self
+    }
+
     /// Build the supercluster options.
     ///
     /// # Returns
     ///
-    /// The supercluster options.
-    pub fn build(self) -> SuperclusterOptions {
-        SuperclusterOptions {
+    /// The supercluster options, otherwise an error if the options are invalid.
+    pub fn build(self) -> Result<SuperclusterOptions, SuperclusterError> {
+        let options = SuperclusterOptions {
             min_zoom: self.min_zoom.unwrap_or(0),
             max_zoom: self.max_zoom.unwrap_or(16),
             min_points: self.min_points.unwrap_or(2),
             radius: self.radius.unwrap_or(40.0),
+            radius_unit: self.radius_unit.unwrap_or(RadiusUnit::Pixels),
             extent: self.extent.unwrap_or(512.0),
             node_size: self.node_size.unwrap_or(64),
             coordinate_system: self.coordinate_system.unwrap_or(CoordinateSystem::LatLng),
-        }
+            wrap_clusters: self.wrap_clusters.unwrap_or(false),
+            algorithm: self.algorithm.unwrap_or(ClusterAlgorithm::Radius),
+            position: self.position.unwrap_or(ClusterPosition::WeightedMean),
+            cluster_bbox: self.cluster_bbox.unwrap_or(false),
+            id_mode: self.id_mode.unwrap_or(ClusterIdMode::Index),
+            geometry_mode: self.geometry_mode.unwrap_or(GeometryMode::PointsOnly),
+            validation: self.validation.unwrap_or(ValidationPolicy::Skip),
+            zoom_schedule: self.zoom_schedule,
+            partition: self.partition,
+            weight: self.weight,
+            #[cfg(feature = "cluster_metadata")]
+            map: self.map,
+            #[cfg(feature = "cluster_metadata")]
+            reduce: self.reduce,
+        };
+
+        options.validate()?;
+
+        Ok(options)
     }
 }
 
@@ -288,15 +958,19 @@ mod tests {
 
     #[test]
     fn test_supercluster_builder_default() {
-        let options = SuperclusterBuilder::default().build();
+        let options = SuperclusterBuilder::default().build().unwrap();
 
         assert_eq!(options.min_zoom, 0);
         assert_eq!(options.max_zoom, 16);
         assert_eq!(options.min_points, 2);
         assert_eq!(options.radius, 40.0);
+        assert_eq!(options.radius_unit, RadiusUnit::Pixels);
         assert_eq!(options.extent, 512.0);
         assert_eq!(options.node_size, 64);
         assert_eq!(options.coordinate_system, CoordinateSystem::LatLng);
+        assert!(!options.wrap_clusters);
+        assert_eq!(options.validation, ValidationPolicy::Skip);
+        assert_eq!(options.weight, None);
     }
 
     #[test]
@@ -309,7 +983,8 @@ mod tests {
             .extent(1024.0)
             .node_size(128)
             .coordinate_system(CoordinateSystem::LatLng)
-            .build();
+            .build()
+            .unwrap();
 
         assert_eq!(options.min_zoom, 1);
         assert_eq!(options.max_zoom, 10);
@@ -319,4 +994,155 @@ mod tests {
         assert_eq!(options.node_size, 128);
         assert_eq!(options.coordinate_system, CoordinateSystem::LatLng);
     }
+
+    #[test]
+    fn test_zoom_schedule() {
+        let options = SuperclusterBuilder::new()
+            .radius(40.0)
+            .min_points(2)
+            .zoom_level(
+                0,
+                ZoomLevel {
+                    radius: Some(120.0),
+                    min_points: Some(5),
+                },
+            )
+            .zoom_level(
+                14,
+                ZoomLevel {
+                    radius: Some(10.0),
+                    ..Default::default()
+                },
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(options.radius_at(0), 120.0);
+        assert_eq!(options.min_points_at(0), 5);
+        assert_eq!(options.radius_at(13), 120.0);
+        assert_eq!(options.radius_at(14), 10.0);
+        assert_eq!(options.min_points_at(14), 2);
+        assert_eq!(options.radius_at(20), 10.0);
+
+        let options = SuperclusterBuilder::new()
+            .min_zoom(3)
+            .zoom_schedule_fn(|zoom| ZoomLevel {
+                radius: Some(100.0 - zoom as f64),
+                min_points: None,
+            })
+            .build()
+            .unwrap();
+
+        assert_eq!(options.radius_at(10), 90.0);
+        assert_eq!(options.min_points_at(10), 2);
+
+        let options = SuperclusterBuilder::new()
+            .zoom_level(
+                5,
+                ZoomLevel {
+                    radius: Some(80.0),
+                    ..Default::default()
+                },
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(options.radius_at(4), 40.0);
+        assert_eq!(options.radius_at(5), 80.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_zoom_schedule_serde() {
+        let schedule = ZoomSchedule::Table(BTreeMap::from([(
+            3,
+            ZoomLevel {
+                radius: Some(60.0),
+                min_points: Some(4),
+            },
+        )]));
+        let json = serde_json::to_string(&schedule).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<ZoomSchedule>(&json).unwrap(),
+            schedule
+        );
+    }
+
+    #[test]
+    fn test_point_weight() {
+        let mut feature = Feature::default();
+        feature.set_property("connectors", 40);
+        feature.set_property("broken", -2);
+
+        let options = SuperclusterBuilder::new()
+            .weight_property("connectors")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            options.weight,
+            Some(PointWeight::Property("connectors".to_string()))
+        );
+        assert_eq!(options.weight.unwrap().weight(&feature), 40.0);
+        assert_eq!(
+            PointWeight::Property("broken".to_string()).weight(&feature),
+            0.0
+        );
+        assert_eq!(
+            PointWeight::Property("missing".to_string()).weight(&feature),
+            1.0
+        );
+
+        let options = SuperclusterBuilder::new()
+            .weight_fn(|_| 2.5)
+            .build()
+            .unwrap();
+
+        assert_eq!(options.weight.unwrap().weight(&feature), 2.5);
+    }
+
+    #[test]
+    fn test_partition_key() {
+        let mut feature = Feature::default();
+        feature.set_property("speed", "fast");
+        feature.set_property("power", 150);
+
+        let options = SuperclusterBuilder::new()
+            .partition_property("speed")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            options.partition,
+            Some(PartitionKey::Property("speed".to_string()))
+        );
+        assert_eq!(
+            options.partition.unwrap().key(&feature),
+            Some("fast".to_string())
+        );
+        assert_eq!(
+            PartitionKey::Property("power".to_string()).key(&feature),
+            Some("150".to_string())
+        );
+        assert_eq!(
+            PartitionKey::Property("missing".to_string()).key(&feature),
+            None
+        );
+
+        let options = SuperclusterBuilder::new()
+            .partition_fn(|feature| {
+                match feature.property("power").and_then(|power| power.as_f64()) {
+                    Some(power) if power >= 50.0 => "fast".to_string(),
+                    _ => "slow".to_string(),
+                }
+            })
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            options.partition.unwrap().key(&feature),
+            Some("fast".to_string())
+        );
+    }
 }
@@ -0,0 +1,72 @@
+//! # Callback module
+//!
+//! Contains the shareable callback wrapper used to store user-provided functions in the configuration settings.
+
+use std::{fmt, ops::Deref, sync::Arc};
+
+/// Shareable user-provided function stored in the configuration settings.
+/// The function is reference-counted, so cloning the options does not clone the closure.
+/// Two callbacks are equal only if they point to the same function.
+pub struct Callback<F: ?Sized>(
+    /// The reference-counted function.
+    Arc<F>,
+);
+
+impl<F: ?Sized> Callback<F> {
+    /// Create a new callback from a reference-counted function.
+    ///
+    /// # Arguments
+    ///
+    /// - `f`: The reference-counted function.
+    ///
+    /// # Returns
+    ///
+    /// New callback wrapping the function.
+    pub fn new(f: Arc<F>) -> Self {
+        Callback(f)
+    }
+}
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Callback(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> Deref for Callback<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+impl<F: ?Sized> PartialEq for Callback<F> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_eq() {
+        let f: Arc<dyn Fn(f64) -> f64> = Arc::new(|v| v * 2.0);
+        let a = Callback::new(f.clone());
+        let b = a.clone();
+        let c: Callback<dyn Fn(f64) -> f64> = Callback::new(Arc::new(|v| v * 2.0));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a(2.0), 4.0);
+        assert_eq!(format!("{:?}", a), "Callback");
+    }
+}
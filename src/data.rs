@@ -0,0 +1,558 @@
+//! # Data module
+//!
+//! Contains the typed columns storing the state of the points and clusters of a zoom level.
+
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Zoom level of a point or cluster that has not been processed yet.
+pub const UNVISITED: u8 = u8::MAX;
+
+/// Index stored in the `u32` columns when there is no index, e.g. a point without a parent cluster.
+pub const NO_INDEX: u32 = u32::MAX;
+
+/// Floating point type of the stored coordinates and cluster positions, `f64` by default.
+///
+/// With the `f32` feature the coordinates are stored as `f32`, halving the memory of the coordinate columns
+/// and of the KD-trees. The normalized coordinates lie in [0, 1], so they are rounded by at most 2^-25,
+/// which is at most 2^(z - 16) tile units at zoom `z` with the default extent of 512,
+/// i.e. at most half a unit up to zoom 15. A rounded tile coordinate can still differ by one unit
+/// when the exact value lies close to the middle of two units, and points lying almost exactly
+/// at the radius from each other may be clustered differently.
+/// The computations, e.g. the weighted means, are still performed in `f64`.
+#[cfg(feature = "f32")]
+pub type Float = f32;
+
+/// Floating point type of the stored coordinates and cluster positions, `f64` by default.
+///
+/// With the `f32` feature the coordinates are stored as `f32`, halving the memory of the coordinate columns
+/// and of the KD-trees.
+#[cfg(not(feature = "f32"))]
+pub type Float = f64;
+
+/// Optional columns of the cluster data.
+/// A column is only filled when the configuration options need it, otherwise it stays empty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Columns {
+    /// Whether the displayed position of the points and clusters is stored.
+    pub position: bool,
+
+    /// Whether the bounding box of the points and clusters is stored.
+    pub bbox: bool,
+
+    /// Whether the member set hash of the points and clusters is stored.
+    pub hash: bool,
+
+    /// Whether the index of the aggregated cluster properties is stored.
+    pub properties: bool,
+}
+
+/// A single point or cluster of the cluster data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClusterRow {
+    /// The normalized x coordinate.
+    pub x: f64,
+
+    /// The normalized y coordinate.
+    pub y: f64,
+
+    /// The last zoom level the point or cluster was processed at, otherwise `None`.
+    pub zoom: Option<u8>,
+
+    /// The index of the input point, or the ID of the cluster.
+    pub id: u64,
+
+    /// The index of the parent cluster in the data of the zoom level below, otherwise `None`.
+    pub parent: Option<usize>,
+
+    /// The number of points.
+    pub count: u32,
+
+    /// The total weight of the points.
+    pub weight: f64,
+
+    /// The index of the partition key, otherwise `None`.
+    pub partition: Option<usize>,
+
+    /// The displayed position as [x, y, weight], where the weight is the one of the point at that position.
+    pub position: [f64; 3],
+
+    /// The bounding box of the points as [min_x, min_y, max_x, max_y].
+    pub bbox: [f64; 4],
+
+    /// The sum of the hashes of the points.
+    pub hash: u64,
+
+    /// The index of the point within a multi point, otherwise `None`.
+    pub part: Option<usize>,
+
+    /// The index of the aggregated cluster properties, otherwise `None`.
+    pub properties: Option<usize>,
+}
+
+/// The points and clusters of a zoom level, stored as typed columns.
+/// The rows are indexed by the KD-tree of the zoom level.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ClusterData {
+    /// The optional columns filled by the data.
+    pub columns: Columns,
+
+    /// The normalized x coordinates.
+    pub x: Vec<Float>,
+
+    /// The normalized y coordinates.
+    pub y: Vec<Float>,
+
+    /// The last zoom levels the rows were processed at, `UNVISITED` if not processed yet.
+    pub zoom: Vec<u8>,
+
+    /// The indices of the input points, or the IDs of the clusters.
+    pub id: Vec<u64>,
+
+    /// The indices of the parent clusters in the data of the zoom level below, `NO_INDEX` if none.
+    pub parent: Vec<u32>,
+
+    /// The number of points.
+    pub count: Vec<u32>,
+
+    /// The total weight of the points.
+    pub weight: Vec<f64>,
+
+    /// The indices of the partition keys, `NO_INDEX` if none.
+    pub partition: Vec<u32>,
+
+    /// The indices of the points within a multi point, `NO_INDEX` if none.
+    pub part: Vec<u32>,
+
+    /// The displayed positions as [x, y, weight].
+    /// Only filled if `columns.position` is set.
+    pub position: Vec<[Float; 3]>,
+
+    /// The bounding boxes of the points as [min_x, min_y, max_x, max_y].
+    /// Only filled if `columns.bbox` is set.
+    pub bbox: Vec<[f64; 4]>,
+
+    /// The sums of the hashes of the points.
+    /// Only filled if `columns.hash` is set.
+    pub hash: Vec<u64>,
+
+    /// The indices of the aggregated cluster properties, `NO_INDEX` if none.
+    /// Only filled if `columns.properties` is set.
+    pub properties: Vec<u32>,
+}
+
+impl ClusterData {
+    /// Create empty cluster data.
+    ///
+    /// # Arguments
+    ///
+    /// - `columns`: The optional columns to fill.
+    ///
+    /// # Returns
+    ///
+    /// New empty cluster data.
+    pub fn new(columns: Columns) -> Self {
+        ClusterData {
+            columns,
+            ..Default::default()
+        }
+    }
+
+    /// Get the number of rows.
+    ///
+    /// # Returns
+    ///
+    /// The number of points and clusters.
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Check whether the data has no rows.
+    ///
+    /// # Returns
+    ///
+    /// `true` if there are no points and clusters, otherwise `false`.
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    /// Append a row.
+    ///
+    /// # Arguments
+    ///
+    /// - `row`: The point or cluster to append.
+    pub fn push(&mut self, row: ClusterRow) {
+        self.x.push(row.x as Float);
+        self.y.push(row.y as Float);
+        self.zoom.push(row.zoom.unwrap_or(UNVISITED));
+        self.id.push(row.id);
+        self.parent.push(to_index(row.parent));
+        self.count.push(row.count);
+        self.weight.push(row.weight);
+        self.partition.push(to_index(row.partition));
+        self.part.push(to_index(row.part));
+
+        if self.columns.position {
+            self.position.push(row.position.map(|value| value as Float));
+        }
+
+        if self.columns.bbox {
+            self.bbox.push(row.bbox);
+        }
+
+        if self.columns.hash {
+            self.hash.push(row.hash);
+        }
+
+        if self.columns.properties {
+            self.properties.push(to_index(row.properties));
+        }
+    }
+
+    /// Get a row.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The point or cluster, with the values of the columns that are not filled derived from the others.
+    pub fn row(&self, k: usize) -> ClusterRow {
+        ClusterRow {
+            x: self.coords(k)[0],
+            y: self.coords(k)[1],
+            zoom: Some(self.zoom[k]).filter(|zoom| *zoom != UNVISITED),
+            id: self.id[k],
+            parent: self.parent(k),
+            count: self.count[k],
+            weight: self.weight[k],
+            partition: self.partition(k),
+            position: self.position(k),
+            bbox: self.bbox(k),
+            hash: self.hash(k),
+            part: self.part(k),
+            properties: self.properties(k),
+        }
+    }
+
+    /// Check whether a row is a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the row has more than one point, otherwise `false`.
+    pub fn is_cluster(&self, k: usize) -> bool {
+        self.count[k] > 1
+    }
+
+    /// Get the coordinates of a row.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The normalized x and y coordinates.
+    pub fn coords(&self, k: usize) -> [f64; 2] {
+        [self.x[k], self.y[k]].map(f64::from)
+    }
+
+    /// Get the index of the parent cluster of a row in the data of the zoom level below.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The index of the parent cluster, otherwise `None`.
+    pub fn parent(&self, k: usize) -> Option<usize> {
+        from_index(self.parent[k])
+    }
+
+    /// Get the index of the partition key of a row.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The index of the partition key, otherwise `None`.
+    pub fn partition(&self, k: usize) -> Option<usize> {
+        from_index(self.partition[k])
+    }
+
+    /// Get the index of a row within a multi point.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The index of the point within the multi point, otherwise `None`.
+    pub fn part(&self, k: usize) -> Option<usize> {
+        from_index(self.part[k])
+    }
+
+    /// Get the displayed position of a row.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The position as [x, y, weight], by default the coordinates and the weight of the row.
+    pub fn position(&self, k: usize) -> [f64; 3] {
+        match self.position.get(k) {
+            Some(position) => position.map(f64::from),
+            None => {
+                let [x, y] = self.coords(k);
+
+                [x, y, self.weight[k]]
+            }
+        }
+    }
+
+    /// Get the bounding box of a row.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The bounding box as [min_x, min_y, max_x, max_y], by default the coordinates of the row.
+    pub fn bbox(&self, k: usize) -> [f64; 4] {
+        match self.bbox.get(k) {
+            Some(bbox) => *bbox,
+            None => {
+                let [x, y] = self.coords(k);
+
+                [x, y, x, y]
+            }
+        }
+    }
+
+    /// Get the member set hash of a row.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The sum of the hashes of the points, by default 0.
+    pub fn hash(&self, k: usize) -> u64 {
+        self.hash.get(k).copied().unwrap_or_default()
+    }
+
+    /// Get the index of the aggregated properties of a row.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the row.
+    ///
+    /// # Returns
+    ///
+    /// The index of the aggregated cluster properties, otherwise `None`.
+    pub fn properties(&self, k: usize) -> Option<usize> {
+        self.properties.get(k).copied().and_then(from_index)
+    }
+
+    /// Reset the clustering state of every row, so the rows can be clustered again.
+    pub fn reset(&mut self) {
+        self.zoom.fill(UNVISITED);
+        self.parent.fill(NO_INDEX);
+    }
+
+    /// Replace a range of rows with other rows.
+    ///
+    /// # Arguments
+    ///
+    /// - `range`: The range of rows to replace.
+    /// - `rows`: The rows to insert, with the same optional columns.
+    pub fn splice(&mut self, range: Range<usize>, rows: ClusterData) {
+        self.x.splice(range.clone(), rows.x);
+        self.y.splice(range.clone(), rows.y);
+        self.zoom.splice(range.clone(), rows.zoom);
+        self.id.splice(range.clone(), rows.id);
+        self.parent.splice(range.clone(), rows.parent);
+        self.count.splice(range.clone(), rows.count);
+        self.weight.splice(range.clone(), rows.weight);
+        self.partition.splice(range.clone(), rows.partition);
+        self.part.splice(range.clone(), rows.part);
+
+        if self.columns.position {
+            self.position.splice(range.clone(), rows.position);
+        }
+
+        if self.columns.bbox {
+            self.bbox.splice(range.clone(), rows.bbox);
+        }
+
+        if self.columns.hash {
+            self.hash.splice(range.clone(), rows.hash);
+        }
+
+        if self.columns.properties {
+            self.properties.splice(range, rows.properties);
+        }
+    }
+
+    /// Append other rows.
+    ///
+    /// # Arguments
+    ///
+    /// - `rows`: The rows to append, with the same optional columns.
+    pub fn append(&mut self, rows: ClusterData) {
+        let len = self.len();
+
+        self.splice(len..len, rows);
+    }
+
+    /// Split the rows in two at the specified index.
+    ///
+    /// # Arguments
+    ///
+    /// - `at`: The index of the first row to split off.
+    ///
+    /// # Returns
+    ///
+    /// The rows from the index on, which are removed from the data.
+    pub fn split_off(&mut self, at: usize) -> ClusterData {
+        let optional = |enabled: bool, at: usize| if enabled { at } else { 0 };
+
+        ClusterData {
+            columns: self.columns,
+            x: self.x.split_off(at),
+            y: self.y.split_off(at),
+            zoom: self.zoom.split_off(at),
+            id: self.id.split_off(at),
+            parent: self.parent.split_off(at),
+            count: self.count.split_off(at),
+            weight: self.weight.split_off(at),
+            partition: self.partition.split_off(at),
+            part: self.part.split_off(at),
+            position: self.position.split_off(optional(self.columns.position, at)),
+            bbox: self.bbox.split_off(optional(self.columns.bbox, at)),
+            hash: self.hash.split_off(optional(self.columns.hash, at)),
+            properties: self
+                .properties
+                .split_off(optional(self.columns.properties, at)),
+        }
+    }
+}
+
+/// Convert an optional index into the value stored in a `u32` column.
+///
+/// # Arguments
+///
+/// - `index`: The optional index.
+///
+/// # Returns
+///
+/// The index, otherwise `NO_INDEX`.
+fn to_index(index: Option<usize>) -> u32 {
+    index.map_or(NO_INDEX, |index| index as u32)
+}
+
+/// Convert the value stored in a `u32` column into an optional index.
+///
+/// # Arguments
+///
+/// - `index`: The stored value.
+///
+/// # Returns
+///
+/// The index, otherwise `None` if the value is `NO_INDEX`.
+fn from_index(index: u32) -> Option<usize> {
+    (index != NO_INDEX).then_some(index as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: u64) -> ClusterRow {
+        ClusterRow {
+            x: id as f64,
+            y: 0.5,
+            zoom: None,
+            id,
+            parent: None,
+            count: 1,
+            weight: 2.0,
+            partition: Some(1),
+            position: [id as f64, 0.5, 2.0],
+            bbox: [id as f64, 0.5, id as f64, 0.5],
+            hash: 7,
+            part: None,
+            properties: None,
+        }
+    }
+
+    #[test]
+    fn test_cluster_data_rows() {
+        let mut data = ClusterData::new(Columns {
+            hash: true,
+            ..Default::default()
+        });
+
+        for id in 0..4 {
+            data.push(row(id));
+        }
+
+        assert_eq!(data.len(), 4);
+        assert_eq!(data.row(2), row(2));
+        assert!(data.position.is_empty());
+        assert_eq!(data.hash, vec![7; 4]);
+
+        data.zoom[1] = 3;
+        data.parent[1] = 0;
+
+        assert_eq!(data.row(1).zoom, Some(3));
+        assert_eq!(data.row(1).parent, Some(0));
+
+        data.reset();
+
+        assert_eq!(data.row(1), row(1));
+    }
+
+    #[test]
+    fn test_cluster_data_splice() {
+        let columns = Columns {
+            bbox: true,
+            ..Default::default()
+        };
+        let mut data = ClusterData::new(columns);
+
+        for id in 0..4 {
+            data.push(row(id));
+        }
+
+        let moved = data.split_off(3);
+        data.splice(1..2, moved);
+
+        assert_eq!(data.id, vec![0, 3, 2]);
+        assert_eq!(data.bbox[1], [3.0, 0.5, 3.0, 0.5]);
+
+        let mut rows = ClusterData::new(columns);
+        rows.push(row(5));
+        data.append(rows);
+
+        assert_eq!(data.id, vec![0, 3, 2, 5]);
+        assert_eq!(data.bbox.len(), 4);
+    }
+}
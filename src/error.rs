@@ -4,6 +4,8 @@
 
 use thiserror::Error;
 
+use crate::InvalidReason;
+
 /// Supercluster error.
 /// Represents the different errors that can occur in the supercluster crate.
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -16,7 +18,45 @@ pub enum SuperclusterError {
     #[error("Tree not found at the specified zoom level.")]
     TreeNotFound,
 
+    /// Point not found with the specified index or ID.
+    #[error("Point not found with the specified index or ID.")]
+    PointNotFound,
+
+    /// Cluster ID lower than the number of input points, or not encoding a cluster zoom level.
+    #[error("Invalid cluster ID.")]
+    InvalidClusterId,
+
+    /// Too many input points to encode the cluster IDs exactly.
+    #[error("Too many input points to encode the cluster IDs.")]
+    TooManyPoints,
+
+    /// Invalid configuration options.
+    #[error("Invalid options: {0}")]
+    InvalidOptions(String),
+
+    /// Input point outside the data range of the Cartesian coordinate system.
+    #[error("Point {0} is outside the data range.")]
+    OutOfRange(usize),
+
+    /// Input point without geometry or with invalid coordinates, rejected by `ValidationPolicy::Reject`.
+    #[error("Feature {index} is invalid: {reason}.")]
+    InvalidFeature {
+        /// The index of the feature in the input points.
+        index: usize,
+
+        /// The reason the feature is invalid.
+        reason: InvalidReason,
+    },
+
     /// Tile not found at the specified coordinates and zoom level.
     #[error("Tile not found at the specified coordinates and zoom level.")]
     TileNotFound,
+
+    /// Input that could not be read.
+    #[error("Failed to read the input: {0}")]
+    Io(String),
+
+    /// Input that could not be parsed.
+    #[error("Failed to parse the input: {0}")]
+    Parse(String),
 }
@@ -0,0 +1,162 @@
+//! # Filter module
+//!
+//! Contains the cache of the filtered cluster hierarchies used by the filtered queries.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::BuildHasherDefault,
+    sync::{Arc, RwLock},
+};
+
+use twox_hash::XxHash64;
+
+use geojson::Feature;
+
+use crate::Supercluster;
+
+/// Cache of the cluster hierarchies built from a subset of the input points.
+/// The hierarchies are keyed by a filter identifier provided by the caller.
+/// The cache can be shared between threads, cloning the cache shares the cached hierarchies.
+pub struct FilterCache<T = Feature>(
+    /// The filtered cluster hierarchies by filter identifier.
+    RwLock<HashMap<String, Arc<Supercluster<T>>, BuildHasherDefault<XxHash64>>>,
+);
+
+impl<T> FilterCache<T> {
+    /// Create a new empty filter cache.
+    ///
+    /// # Returns
+    ///
+    /// New filter cache.
+    pub fn new() -> Self {
+        FilterCache(RwLock::default())
+    }
+
+    /// Get the cluster hierarchy cached for a filter.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter.
+    ///
+    /// # Returns
+    ///
+    /// The filtered cluster hierarchy, otherwise `None` if the filter is not cached.
+    pub fn get(&self, filter_id: &str) -> Option<Arc<Supercluster<T>>> {
+        self.0
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .get(filter_id)
+            .cloned()
+    }
+
+    /// Cache the cluster hierarchy of a filter.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter.
+    /// - `index`: The filtered cluster hierarchy.
+    pub fn insert(&self, filter_id: &str, index: Arc<Supercluster<T>>) {
+        self.0
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(filter_id.to_string(), index);
+    }
+
+    /// Remove the cluster hierarchy cached for a filter.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter.
+    ///
+    /// # Returns
+    ///
+    /// The removed cluster hierarchy, otherwise `None` if the filter is not cached.
+    pub fn remove(&self, filter_id: &str) -> Option<Arc<Supercluster<T>>> {
+        self.0
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .remove(filter_id)
+    }
+
+    /// Remove all the cached cluster hierarchies.
+    pub fn clear(&self) {
+        self.0
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .clear();
+    }
+
+    /// Get the number of cached cluster hierarchies.
+    ///
+    /// # Returns
+    ///
+    /// The number of cached filters.
+    pub fn len(&self) -> usize {
+        self.0
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .len()
+    }
+
+    /// Check whether the cache is empty.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no filter is cached, otherwise `false`.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for FilterCache<T> {
+    fn default() -> Self {
+        FilterCache::new()
+    }
+}
+
+impl<T> Clone for FilterCache<T> {
+    fn clone(&self) -> Self {
+        FilterCache(RwLock::new(
+            self.0
+                .read()
+                .unwrap_or_else(|error| error.into_inner())
+                .clone(),
+        ))
+    }
+}
+
+impl<T> fmt::Debug for FilterCache<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let filters = self.0.read().unwrap_or_else(|error| error.into_inner());
+
+        f.debug_set().entries(filters.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_cache() {
+        let cache: FilterCache = FilterCache::new();
+        let index = Arc::new(Supercluster::new(Supercluster::builder().build().unwrap()));
+
+        assert!(cache.is_empty());
+
+        cache.insert("available", index.clone());
+
+        let clone = cache.clone();
+
+        assert_eq!(cache.len(), 1);
+        assert!(Arc::ptr_eq(&clone.get("available").unwrap(), &index));
+        assert_eq!(format!("{:?}", cache), "{\"available\"}");
+
+        cache.clear();
+
+        assert!(cache.get("available").is_none());
+        assert!(clone.remove("available").is_some());
+        assert!(clone.is_empty());
+    }
+}
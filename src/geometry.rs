@@ -0,0 +1,537 @@
+//! # Geometry module
+//!
+//! This module contains the handling of input features whose geometry is not a point.
+//! Multi points are expanded into one leaf per point, while lines, polygons and geometry collections
+//! are clustered at a single anchor point computed from their coordinates.
+
+use geojson::{LineStringType, PointType, PolygonType, Value};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Handling of the input features whose geometry is not a point.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum GeometryMode {
+    /// Ignore the features that are not points, like the JavaScript library.
+    #[default]
+    PointsOnly,
+
+    /// Expand multi points into one leaf per point, and cluster the other geometries at an anchor point.
+    /// Unclustered features keep their original geometry in the query results.
+    Anchored {
+        /// The point at which lines, polygons and geometry collections are clustered.
+        anchor: GeometryAnchor,
+    },
+}
+
+/// The point at which a line, polygon or geometry collection is clustered.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum GeometryAnchor {
+    /// Centroid weighted by area for polygons and by length for lines.
+    /// The centroid of a concave polygon may lie outside of it.
+    #[default]
+    Centroid,
+
+    /// Point inside the largest polygon, or halfway along the longest line.
+    LabelPoint,
+
+    /// Center of the bounding box of the geometry.
+    BboxCenter,
+}
+
+impl GeometryAnchor {
+    /// Compute the anchor point of a geometry.
+    ///
+    /// # Arguments
+    ///
+    /// - `value`: The GeoJSON geometry value.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates of the anchor point, otherwise `None` if the geometry has no coordinates.
+    pub fn anchor(&self, value: &Value) -> Option<PointType> {
+        let parts = Parts::new(value);
+
+        match self {
+            GeometryAnchor::Centroid => parts.centroid(),
+            GeometryAnchor::LabelPoint => parts.label_point(),
+            GeometryAnchor::BboxCenter => parts.bbox_center(),
+        }
+    }
+}
+
+/// The simple geometries contained in a GeoJSON geometry value.
+struct Parts<'a> {
+    /// The points of the geometry.
+    points: Vec<&'a PointType>,
+
+    /// The lines of the geometry.
+    lines: Vec<&'a LineStringType>,
+
+    /// The polygons of the geometry.
+    polygons: Vec<&'a PolygonType>,
+}
+
+impl<'a> Parts<'a> {
+    /// Split a geometry value into its simple geometries, flattening multi geometries and collections.
+    ///
+    /// # Arguments
+    ///
+    /// - `value`: The GeoJSON geometry value.
+    ///
+    /// # Returns
+    ///
+    /// The simple geometries of the value.
+    fn new(value: &'a Value) -> Self {
+        let mut parts = Parts {
+            points: vec![],
+            lines: vec![],
+            polygons: vec![],
+        };
+
+        parts.add(value);
+
+        parts
+    }
+
+    /// Add the simple geometries of a geometry value.
+    ///
+    /// # Arguments
+    ///
+    /// - `value`: The GeoJSON geometry value.
+    fn add(&mut self, value: &'a Value) {
+        match value {
+            Value::Point(point) => self.points.push(point),
+            Value::MultiPoint(points) => self.points.extend(points),
+            Value::LineString(line) => self.lines.push(line),
+            Value::MultiLineString(lines) => self.lines.extend(lines),
+            Value::Polygon(polygon) => self.polygons.push(polygon),
+            Value::MultiPolygon(polygons) => self.polygons.extend(polygons),
+            Value::GeometryCollection(geometries) => {
+                for geometry in geometries {
+                    self.add(&geometry.value);
+                }
+            }
+        }
+    }
+
+    /// Iterate over every position of the simple geometries.
+    ///
+    /// # Returns
+    ///
+    /// The positions with at least two coordinates.
+    fn positions(&self) -> impl Iterator<Item = &'a PointType> + '_ {
+        self.points
+            .iter()
+            .copied()
+            .chain(self.lines.iter().flat_map(|line| line.iter()))
+            .chain(
+                self.polygons
+                    .iter()
+                    .flat_map(|polygon| polygon.iter().flatten()),
+            )
+            .filter(|position| position.len() >= 2)
+    }
+
+    /// Compute the centroid, using the polygons if they have an area, otherwise the lines if they have a length,
+    /// otherwise the mean of the positions.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates of the centroid, otherwise `None` if there are no positions.
+    fn centroid(&self) -> Option<PointType> {
+        let (mut area, mut x, mut y) = (0.0, 0.0, 0.0);
+
+        for polygon in &self.polygons {
+            for (r, ring) in polygon.iter().enumerate() {
+                // Holes are subtracted from the exterior ring
+                let sign = if r == 0 { 1.0 } else { -1.0 };
+                let (ring_area, cx, cy) = ring_centroid(ring);
+
+                area += sign * ring_area;
+                x += sign * ring_area * cx;
+                y += sign * ring_area * cy;
+            }
+        }
+
+        if area > 0.0 {
+            return Some(vec![x / area, y / area]);
+        }
+
+        let (mut length, mut x, mut y) = (0.0, 0.0, 0.0);
+
+        for line in &self.lines {
+            for segment in line.windows(2) {
+                let (a, b) = (&segment[0], &segment[1]);
+                let segment_length = distance(a, b);
+
+                length += segment_length;
+                x += segment_length * (a[0] + b[0]) / 2.0;
+                y += segment_length * (a[1] + b[1]) / 2.0;
+            }
+        }
+
+        if length > 0.0 {
+            return Some(vec![x / length, y / length]);
+        }
+
+        let (mut count, mut x, mut y) = (0.0, 0.0, 0.0);
+
+        for position in self.positions() {
+            count += 1.0;
+            x += position[0];
+            y += position[1];
+        }
+
+        (count > 0.0).then(|| vec![x / count, y / count])
+    }
+
+    /// Compute a label point inside the largest polygon, otherwise halfway along the longest line,
+    /// otherwise the point nearest to the centroid.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates of the label point, otherwise `None` if there are no positions.
+    fn label_point(&self) -> Option<PointType> {
+        let largest = self
+            .polygons
+            .iter()
+            .map(|polygon| (polygon_area(polygon), polygon))
+            .filter(|(area, _)| *area > 0.0)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let Some((_, polygon)) = largest {
+            let centroid = Parts {
+                points: vec![],
+                lines: vec![],
+                polygons: vec![polygon],
+            }
+            .centroid()?;
+            let (min_y, max_y) = polygon[0]
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+                    (min.min(p[1]), max.max(p[1]))
+                });
+
+            // Take the midpoint of the widest interior span across a few horizontal scan lines,
+            // preferring the scan line through the centroid on ties
+            let widest = [centroid[1], (min_y + max_y) / 2.0]
+                .into_iter()
+                .flat_map(|y| {
+                    interior_spans(polygon, y)
+                        .into_iter()
+                        .map(move |(x0, x1)| (x1 - x0, vec![(x0 + x1) / 2.0, y]))
+                })
+                .min_by(|a, b| b.0.total_cmp(&a.0));
+
+            return widest.map(|(_, point)| point).or(Some(centroid));
+        }
+
+        let longest = self
+            .lines
+            .iter()
+            .map(|line| (line.windows(2).map(|s| distance(&s[0], &s[1])).sum(), line))
+            .filter(|(length, _)| *length > 0.0)
+            .max_by(|a: &(f64, _), b| a.0.total_cmp(&b.0));
+
+        if let Some((length, line)) = longest {
+            let mut remaining = length / 2.0;
+
+            for segment in line.windows(2) {
+                let (a, b) = (&segment[0], &segment[1]);
+                let segment_length = distance(a, b);
+
+                if remaining <= segment_length && segment_length > 0.0 {
+                    let t = remaining / segment_length;
+
+                    return Some(vec![a[0] + t * (b[0] - a[0]), a[1] + t * (b[1] - a[1])]);
+                }
+
+                remaining -= segment_length;
+            }
+        }
+
+        let centroid = self.centroid()?;
+
+        self.positions()
+            .min_by(|a, b| distance(a, &centroid).total_cmp(&distance(b, &centroid)))
+            .map(|position| vec![position[0], position[1]])
+    }
+
+    /// Compute the center of the bounding box.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates of the bounding box center, otherwise `None` if there are no positions.
+    fn bbox_center(&self) -> Option<PointType> {
+        let mut bbox = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+
+        for position in self.positions() {
+            bbox = [
+                bbox[0].min(position[0]),
+                bbox[1].min(position[1]),
+                bbox[2].max(position[0]),
+                bbox[3].max(position[1]),
+            ];
+        }
+
+        bbox[0]
+            .is_finite()
+            .then(|| vec![(bbox[0] + bbox[2]) / 2.0, (bbox[1] + bbox[3]) / 2.0])
+    }
+}
+
+/// Collect every position of a geometry, e.g. to compute the convex hull of its coordinates.
+///
+/// # Arguments
+///
+/// - `value`: The GeoJSON geometry value.
+///
+/// # Returns
+///
+/// The positions with at least two coordinates.
+pub(crate) fn get_positions(value: &Value) -> Vec<[f64; 2]> {
+    Parts::new(value)
+        .positions()
+        .map(|position| [position[0], position[1]])
+        .collect()
+}
+
+/// Transform every position of a geometry, keeping its structure.
+///
+/// # Arguments
+///
+/// - `value`: The GeoJSON geometry value.
+/// - `f`: The function transforming a position.
+///
+/// # Returns
+///
+/// The geometry value with the transformed positions.
+pub(crate) fn map_positions<F>(value: &Value, f: &F) -> Value
+where
+    F: Fn(&[f64]) -> PointType,
+{
+    let line = |line: &LineStringType| line.iter().map(|p| f(p)).collect::<LineStringType>();
+    let polygon = |polygon: &PolygonType| polygon.iter().map(line).collect::<PolygonType>();
+
+    match value {
+        Value::Point(point) => Value::Point(f(point)),
+        Value::MultiPoint(points) => Value::MultiPoint(line(points)),
+        Value::LineString(points) => Value::LineString(line(points)),
+        Value::MultiLineString(lines) => Value::MultiLineString(lines.iter().map(line).collect()),
+        Value::Polygon(rings) => Value::Polygon(polygon(rings)),
+        Value::MultiPolygon(polygons) => {
+            Value::MultiPolygon(polygons.iter().map(polygon).collect())
+        }
+        Value::GeometryCollection(geometries) => Value::GeometryCollection(
+            geometries
+                .iter()
+                .map(|geometry| geojson::Geometry::new(map_positions(&geometry.value, f)))
+                .collect(),
+        ),
+    }
+}
+
+/// Compute the area and centroid of a ring with the shoelace formula.
+///
+/// # Arguments
+///
+/// - `ring`: The positions of the ring.
+///
+/// # Returns
+///
+/// The absolute area of the ring and the x and y coordinates of its centroid.
+fn ring_centroid(ring: &[PointType]) -> (f64, f64, f64) {
+    let origin = match ring.first() {
+        Some(origin) if origin.len() >= 2 => origin,
+        _ => return (0.0, 0.0, 0.0),
+    };
+    let (mut area, mut x, mut y) = (0.0, 0.0, 0.0);
+
+    // Translate the ring to its first position to limit the floating point error
+    for segment in ring.windows(2) {
+        let (ax, ay) = (segment[0][0] - origin[0], segment[0][1] - origin[1]);
+        let (bx, by) = (segment[1][0] - origin[0], segment[1][1] - origin[1]);
+        let cross = ax * by - bx * ay;
+
+        area += cross;
+        x += (ax + bx) * cross;
+        y += (ay + by) * cross;
+    }
+
+    if area == 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+
+    (
+        area.abs() / 2.0,
+        x / (3.0 * area) + origin[0],
+        y / (3.0 * area) + origin[1],
+    )
+}
+
+/// Compute the area of a polygon, subtracting its holes.
+///
+/// # Arguments
+///
+/// - `polygon`: The rings of the polygon.
+///
+/// # Returns
+///
+/// The area of the polygon.
+fn polygon_area(polygon: &PolygonType) -> f64 {
+    polygon
+        .iter()
+        .enumerate()
+        .map(|(r, ring)| {
+            let area = ring_centroid(ring).0;
+
+            if r == 0 {
+                area
+            } else {
+                -area
+            }
+        })
+        .sum()
+}
+
+/// Compute the spans of a horizontal line inside a polygon with the even-odd rule.
+///
+/// # Arguments
+///
+/// - `polygon`: The rings of the polygon.
+/// - `y`: The y coordinate of the horizontal line.
+///
+/// # Returns
+///
+/// The start and end x coordinates of the spans inside the polygon.
+fn interior_spans(polygon: &PolygonType, y: f64) -> Vec<(f64, f64)> {
+    let mut crossings: Vec<f64> = polygon
+        .iter()
+        .flat_map(|ring| ring.windows(2))
+        .filter(|segment| (segment[0][1] > y) != (segment[1][1] > y))
+        .map(|segment| {
+            let (a, b) = (&segment[0], &segment[1]);
+
+            a[0] + (y - a[1]) * (b[0] - a[0]) / (b[1] - a[1])
+        })
+        .collect();
+
+    crossings.sort_by(f64::total_cmp);
+
+    crossings
+        .chunks_exact(2)
+        .map(|span| (span[0], span[1]))
+        .collect()
+}
+
+/// Compute the euclidean distance between two positions.
+///
+/// # Arguments
+///
+/// - `a`: The first position.
+/// - `b`: The second position.
+///
+/// # Returns
+///
+/// The distance between the positions.
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    (b[0] - a[0]).hypot(b[1] - a[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polygon_anchors() {
+        let square = |x: f64, y: f64, size: f64| {
+            vec![
+                vec![x, y],
+                vec![x + size, y],
+                vec![x + size, y + size],
+                vec![x, y + size],
+                vec![x, y],
+            ]
+        };
+        let polygon = Value::Polygon(vec![square(0.0, 0.0, 4.0), square(2.0, 0.0, 2.0)]);
+
+        // The hole removes the lower right quarter, leaving an L shape
+        let centroid = GeometryAnchor::Centroid.anchor(&polygon).unwrap();
+
+        assert!((centroid[0] - 5.0 / 3.0).abs() < 1e-12);
+        assert!((centroid[1] - 7.0 / 3.0).abs() < 1e-12);
+        assert_eq!(
+            GeometryAnchor::BboxCenter.anchor(&polygon),
+            Some(vec![2.0, 2.0])
+        );
+
+        let label = GeometryAnchor::LabelPoint.anchor(&polygon).unwrap();
+
+        assert_eq!(label[0], 2.0);
+        assert!((label[1] - 7.0 / 3.0).abs() < 1e-12);
+
+        // The centroid of a C shape lies in its opening, but the label point stays inside
+        let opening = vec![
+            vec![2.0, 2.0],
+            vec![6.0, 2.0],
+            vec![6.0, 4.0],
+            vec![2.0, 4.0],
+            vec![2.0, 2.0],
+        ];
+        let polygon = Value::Polygon(vec![square(0.0, 0.0, 6.0), opening]);
+        let centroid = GeometryAnchor::Centroid.anchor(&polygon).unwrap();
+
+        assert!(centroid[0] > 2.0 && centroid[1] > 2.0 && centroid[1] < 4.0);
+        assert_eq!(
+            GeometryAnchor::LabelPoint.anchor(&polygon),
+            Some(vec![1.0, 3.0])
+        );
+    }
+
+    #[test]
+    fn test_line_and_point_anchors() {
+        let line = Value::LineString(vec![vec![0.0, 0.0], vec![4.0, 0.0], vec![4.0, 2.0]]);
+
+        assert_eq!(
+            GeometryAnchor::Centroid.anchor(&line),
+            Some(vec![8.0 / 3.0, 1.0 / 3.0])
+        );
+        assert_eq!(
+            GeometryAnchor::LabelPoint.anchor(&line),
+            Some(vec![3.0, 0.0])
+        );
+
+        let points = Value::MultiPoint(vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![5.0, 0.0]]);
+
+        assert_eq!(
+            GeometryAnchor::Centroid.anchor(&points),
+            Some(vec![2.0, 0.0])
+        );
+        assert_eq!(
+            GeometryAnchor::LabelPoint.anchor(&points),
+            Some(vec![1.0, 0.0])
+        );
+        assert_eq!(
+            GeometryAnchor::BboxCenter.anchor(&Value::GeometryCollection(vec![])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_map_positions() {
+        let polygon = Value::MultiPolygon(vec![vec![vec![vec![1.0, 2.0], vec![3.0, 4.0]]]]);
+
+        assert_eq!(
+            map_positions(&polygon, &|p| vec![p[0] * 2.0, p[1] * 2.0]),
+            Value::MultiPolygon(vec![vec![vec![vec![2.0, 4.0], vec![6.0, 8.0]]]])
+        );
+        assert_eq!(get_positions(&polygon), vec![[1.0, 2.0], [3.0, 4.0]]);
+    }
+}
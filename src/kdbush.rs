@@ -5,8 +5,7 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-/// Array of coordinates with longitude as first value and latitude as second one.
-type Point = [f64; 2];
+use crate::{ClusterData, Float};
 
 /// Static spatial index for 2D points based on a flat KD-tree.
 /// The KD-tree is used to perform range and within queries on the points.
@@ -19,20 +18,17 @@ pub struct KDBush {
     pub node_size: usize,
 
     /// A list of point IDs used to reference points in the KD-tree.
-    /// The IDs are used to retrieve the original points from the index.
-    pub ids: Vec<usize>,
+    /// The IDs are the indices of the points in the order they were added, and of the rows of the data.
+    pub ids: Vec<u32>,
 
     /// A flat array containing the X and Y coordinates of all points in interleaved order.
-    /// The coordinates are stored as [x1, y1, x2, y2, ..., xn, yn].
-    pub coords: Vec<f64>,
+    /// The coordinates are stored as [x1, y1, x2, y2, ..., xn, yn], in the order they were added until the index is built.
+    /// The coordinates are stored as `f32` with the `f32` feature, see `Float`.
+    pub coords: Vec<Float>,
 
-    /// A list of 2D points represented as an array of [longitude, latitude] coordinates.
-    /// The points are used to build the KD-tree index.
-    pub points: Vec<Point>,
-
-    /// A list of additional data associated with the points (e.g., properties).
-    /// The data is stored in the same order as the points.
-    pub data: Vec<f64>,
+    /// The points and clusters indexed by the KD-tree, stored as typed columns.
+    /// The rows are stored in the same order as the points were added.
+    pub data: ClusterData,
 }
 
 impl KDBush {
@@ -50,9 +46,8 @@ impl KDBush {
         KDBush {
             node_size,
             ids: Vec::with_capacity(size_hint),
-            points: Vec::with_capacity(size_hint),
-            coords: Vec::with_capacity(size_hint),
-            data: Vec::with_capacity(size_hint),
+            coords: Vec::with_capacity(2 * size_hint),
+            data: ClusterData::default(),
         }
     }
 
@@ -63,7 +58,8 @@ impl KDBush {
     /// - `x`: The X-coordinate of the point (longitude).
     /// - `y`: The Y-coordinate of the point (latitude).
     pub fn add_point(&mut self, x: f64, y: f64) {
-        self.points.push([x, y]);
+        self.coords.push(x as Float);
+        self.coords.push(y as Float);
     }
 
     /// Build the KD-tree index from the added points.
@@ -72,15 +68,15 @@ impl KDBush {
     /// After calling this method, the index will be ready for range and within queries.
     pub fn build_index(&mut self) {
         #[cfg(feature = "log")]
-        log::debug!("Building KDBush index with {} points", self.points.len());
-
-        self.coords = vec![0.0; 2 * self.points.len()];
+        log::debug!(
+            "Building KDBush index with {} points",
+            self.coords.len() / 2
+        );
 
-        for (i, point) in self.points.iter().enumerate() {
-            self.ids.push(i);
+        self.ids = (0..(self.coords.len() / 2) as u32).collect();
 
-            self.coords[i * 2] = point[0];
-            self.coords[i * 2 + 1] = point[1];
+        if self.ids.is_empty() {
+            return;
         }
 
         self.sort(0, self.ids.len() - 1, 0);
@@ -111,6 +107,10 @@ impl KDBush {
             max_y
         );
 
+        if self.ids.is_empty() {
+            return vec![];
+        }
+
         let mut stack = vec![(0, self.ids.len() - 1, 0)];
         let mut result: Vec<usize> = Vec::new();
         let mut x: f64;
@@ -119,22 +119,20 @@ impl KDBush {
         while let Some((axis, right, left)) = stack.pop() {
             if right - left <= self.node_size {
                 for i in left..=right {
-                    x = self.coords[i * 2];
-                    y = self.coords[i * 2 + 1];
+                    [x, y] = self.point(i);
 
                     if x >= min_x && x <= max_x && y >= min_y && y <= max_y {
-                        result.push(self.ids[i]);
+                        result.push(self.ids[i] as usize);
                     }
                 }
                 continue;
             }
 
             let m = (left + right) >> 1;
-            x = self.coords[m * 2];
-            y = self.coords[m * 2 + 1];
+            [x, y] = self.point(m);
 
             if x >= min_x && x <= max_x && y >= min_y && y <= max_y {
-                result.push(self.ids[m]);
+                result.push(self.ids[m] as usize);
             }
 
             let next_axis = (axis + 1) % 2;
@@ -171,6 +169,10 @@ impl KDBush {
             qy
         );
 
+        if self.ids.is_empty() {
+            return vec![];
+        }
+
         let mut stack = vec![(0, self.ids.len() - 1, 0)];
         let mut result: Vec<usize> = Vec::new();
         let r2 = radius * radius;
@@ -178,12 +180,11 @@ impl KDBush {
         while let Some((axis, right, left)) = stack.pop() {
             if right - left <= self.node_size {
                 for i in left..=right {
-                    let x = self.coords[i * 2];
-                    let y = self.coords[i * 2 + 1];
+                    let [x, y] = self.point(i);
                     let dst = KDBush::sq_dist(x, y, qx, qy);
 
                     if dst <= r2 {
-                        result.push(self.ids[i]);
+                        result.push(self.ids[i] as usize);
                     }
                 }
 
@@ -191,11 +192,10 @@ impl KDBush {
             }
 
             let m = (left + right) >> 1;
-            let x = self.coords[m * 2];
-            let y = self.coords[m * 2 + 1];
+            let [x, y] = self.point(m);
 
             if KDBush::sq_dist(x, y, qx, qy) <= r2 {
-                result.push(self.ids[m]);
+                result.push(self.ids[m] as usize);
             }
 
             let next_axis = (axis + 1) % 2;
@@ -212,6 +212,52 @@ impl KDBush {
         result
     }
 
+    /// Find all point indices within a given radius from a query point, with the X axis wrapping around from 1 to 0.
+    /// Used for the normalized longitudes, so points on both sides of the antimeridian are found.
+    ///
+    /// # Arguments
+    ///
+    /// - `qx`: The normalized X-coordinate of the query point.
+    /// - `qy`: The normalized Y-coordinate of the query point.
+    /// - `radius`: The radius around the query point.
+    ///
+    /// # Returns
+    ///
+    /// A vector of point indices that fall within the specified radius from the query point, across the wrapped edge.
+    pub fn within_wrapped(&self, qx: f64, qy: f64, radius: f64) -> Vec<usize> {
+        let mut result = self.within(qx, qy, radius);
+        let len = result.len();
+
+        if qx - radius < 0.0 {
+            result.extend(self.within(qx + 1.0, qy, radius));
+        }
+
+        if qx + radius > 1.0 {
+            result.extend(self.within(qx - 1.0, qy, radius));
+        }
+
+        // A radius wider than half the axis finds the same points on both sides
+        if result.len() > len {
+            result.sort_unstable();
+            result.dedup();
+        }
+
+        result
+    }
+
+    /// Get the coordinates of a point in the sorted index.
+    ///
+    /// # Arguments
+    ///
+    /// - `i`: The index of the point in the sorted index.
+    ///
+    /// # Returns
+    ///
+    /// The X and Y coordinates of the point.
+    fn point(&self, i: usize) -> [f64; 2] {
+        [self.coords[i * 2], self.coords[i * 2 + 1]].map(f64::from)
+    }
+
     /// Sort points in the KD-tree along a specified axis.
     ///
     /// This method sorts the points in the KD-tree along a specified axis (0 for X or 1 for Y).
@@ -222,7 +268,141 @@ impl KDBush {
     /// - `right`: The right index for the range of points to be sorted.
     /// - `axis`: The axis along which the points should be sorted (0 for X or 1 for Y).
     fn sort(&mut self, left: usize, right: usize, axis: usize) {
-        if right - left <= self.node_size {
+        let node_size = self.node_size;
+
+        self.partition().sort(
+            left,
+            right,
+            axis,
+            node_size,
+            #[cfg(feature = "rayon")]
+            PARALLEL_SORT_THRESHOLD,
+        );
+    }
+
+    /// Select the k-th element along a specified axis within a range of indices.
+    ///
+    /// This method selects the k-th element along the specified axis (0 for X or 1 for Y)
+    /// within the given range of indices.
+    ///
+    /// # Arguments
+    ///
+    /// - `k`: The index of the element to be selected.
+    /// - `left`: The left index for the range of points.
+    /// - `right`: The right index for the range of points.
+    /// - `axis`: The axis along which the selection should be performed (0 for X or 1 for Y).
+    #[cfg(test)]
+    fn select(&mut self, k: usize, left: usize, right: usize, axis: usize) {
+        self.partition().select(k, left, right, axis);
+    }
+
+    /// Return the maximum of two values.
+    ///
+    /// # Arguments
+    ///
+    /// - `a`: The first value.
+    /// - `b`: The second value.
+    ///
+    /// # Returns
+    ///
+    /// The maximum of the two values.
+    fn get_max(a: usize, b: usize) -> usize {
+        if a > b {
+            a
+        } else {
+            b
+        }
+    }
+
+    /// Return the minimum of two values.
+    ///
+    /// # Arguments
+    ///
+    /// - `a`: The first value.
+    /// - `b`: The second value.
+    ///
+    /// # Returns
+    ///
+    /// The minimum of the two values.
+    fn get_min(a: usize, b: usize) -> usize {
+        if a < b {
+            a
+        } else {
+            b
+        }
+    }
+
+    /// Borrow the IDs and coordinates of all points as a partition of the KD-tree.
+    ///
+    /// # Returns
+    ///
+    /// The partition covering all points of the KD-tree.
+    fn partition(&mut self) -> Partition<'_> {
+        Partition {
+            ids: &mut self.ids,
+            coords: &mut self.coords,
+            base: 0,
+        }
+    }
+
+    /// Compute the square of the Euclidean distance between two points in a 2D space.
+    ///
+    /// # Arguments
+    ///
+    /// - `ax`: The x-coordinate of the first point.
+    /// - `ay`: The y-coordinate of the first point.
+    /// - `bx`: The x-coordinate of the second point.
+    /// - `by`: The y-coordinate of the second point.
+    ///
+    /// # Returns
+    ///
+    /// The square of the Euclidean distance between the two points.
+    fn sq_dist(ax: f64, ay: f64, bx: f64, by: f64) -> f64 {
+        let dx = ax - bx;
+        let dy = ay - by;
+
+        dx * dx + dy * dy
+    }
+}
+
+/// Minimum number of points in a range before its halves are sorted in parallel.
+#[cfg(feature = "rayon")]
+const PARALLEL_SORT_THRESHOLD: usize = 8192;
+
+/// A contiguous range of the KD-tree IDs and coordinates being sorted.
+/// Indices are absolute positions in the KD-tree, so disjoint partitions can be sorted independently
+/// while producing exactly the same order as sorting the whole tree at once.
+struct Partition<'a> {
+    /// The IDs of the points in the partition.
+    ids: &'a mut [u32],
+
+    /// The interleaved coordinates of the points in the partition.
+    coords: &'a mut [Float],
+
+    /// The absolute index of the first point in the partition.
+    base: usize,
+}
+
+impl Partition<'_> {
+    /// Sort points in the partition along a specified axis.
+    /// With the `rayon` feature, the two halves of large ranges are sorted in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// - `left`: The left index for the range of points to be sorted.
+    /// - `right`: The right index for the range of points to be sorted.
+    /// - `axis`: The axis along which the points should be sorted (0 for X or 1 for Y).
+    /// - `node_size`: The maximum number of points in a leaf node of the KD-tree.
+    /// - `parallel_size`: The minimum number of points in a range before its halves are sorted in parallel.
+    fn sort(
+        &mut self,
+        left: usize,
+        right: usize,
+        axis: usize,
+        node_size: usize,
+        #[cfg(feature = "rayon")] parallel_size: usize,
+    ) {
+        if right - left <= node_size {
             return;
         }
 
@@ -230,14 +410,65 @@ impl KDBush {
 
         self.select(m, left, right, axis);
 
-        self.sort(left, m - 1, 1 - axis);
-        self.sort(m + 1, right, 1 - axis);
+        #[cfg(feature = "rayon")]
+        if right - left > parallel_size {
+            let (mut lower, mut upper) = self.split(m);
+
+            rayon::join(
+                || lower.sort(left, m - 1, 1 - axis, node_size, parallel_size),
+                || upper.sort(m + 1, right, 1 - axis, node_size, parallel_size),
+            );
+
+            return;
+        }
+
+        self.sort(
+            left,
+            m - 1,
+            1 - axis,
+            node_size,
+            #[cfg(feature = "rayon")]
+            parallel_size,
+        );
+        self.sort(
+            m + 1,
+            right,
+            1 - axis,
+            node_size,
+            #[cfg(feature = "rayon")]
+            parallel_size,
+        );
     }
 
-    /// Select the k-th element along a specified axis within a range of indices.
+    /// Split the partition into the points before and after the specified index, excluding it.
     ///
-    /// This method selects the k-th element along the specified axis (0 for X or 1 for Y)
-    /// within the given range of indices.
+    /// # Arguments
+    ///
+    /// - `m`: The absolute index of the point to split at.
+    ///
+    /// # Returns
+    ///
+    /// The partitions before and after the point.
+    #[cfg(feature = "rayon")]
+    fn split(&mut self, m: usize) -> (Partition<'_>, Partition<'_>) {
+        let (lower_ids, upper_ids) = self.ids.split_at_mut(m - self.base);
+        let (lower_coords, upper_coords) = self.coords.split_at_mut(2 * (m - self.base));
+
+        (
+            Partition {
+                ids: lower_ids,
+                coords: lower_coords,
+                base: self.base,
+            },
+            Partition {
+                ids: &mut upper_ids[1..],
+                coords: &mut upper_coords[2..],
+                base: m + 1,
+            },
+        )
+    }
+
+    /// Select the k-th element along a specified axis within a range of indices.
     ///
     /// # Arguments
     ///
@@ -274,13 +505,13 @@ impl KDBush {
                 self.select(k, new_left, new_right, axis);
             }
 
-            let t = self.coords[2 * k + axis];
+            let t = self.coord(k, axis);
             let mut i = left;
             let mut j = right;
 
             self.swap_item(left, k);
 
-            if self.coords[2 * right + axis] > t {
+            if self.coord(right, axis) > t {
                 self.swap_item(left, right);
             }
 
@@ -290,16 +521,16 @@ impl KDBush {
                 i += 1;
                 j -= 1;
 
-                while self.coords[2 * i + axis] < t {
+                while self.coord(i, axis) < t {
                     i += 1;
                 }
 
-                while self.coords[2 * j + axis] > t {
+                while self.coord(j, axis) > t {
                     j -= 1;
                 }
             }
 
-            if self.coords[2 * left + axis] == t {
+            if self.coord(left, axis) == t {
                 self.swap_item(left, j);
             } else {
                 j += 1;
@@ -315,73 +546,34 @@ impl KDBush {
         }
     }
 
-    /// Return the maximum of two values.
+    /// Get the coordinate of a point along a specified axis.
     ///
     /// # Arguments
     ///
-    /// - `a`: The first value.
-    /// - `b`: The second value.
+    /// - `i`: The absolute index of the point.
+    /// - `axis`: The axis of the coordinate (0 for X or 1 for Y).
     ///
     /// # Returns
     ///
-    /// The maximum of the two values.
-    fn get_max(a: usize, b: usize) -> usize {
-        if a > b {
-            a
-        } else {
-            b
-        }
+    /// The coordinate value.
+    fn coord(&self, i: usize, axis: usize) -> Float {
+        self.coords[2 * (i - self.base) + axis]
     }
 
-    /// Return the minimum of two values.
+    /// Swap the elements at two specified indices in the partition.
     ///
     /// # Arguments
     ///
-    /// - `a`: The first value.
-    /// - `b`: The second value.
-    ///
-    /// # Returns
-    ///
-    /// The minimum of the two values.
-    fn get_min(a: usize, b: usize) -> usize {
-        if a < b {
-            a
-        } else {
-            b
-        }
-    }
-
-    /// Swap the elements at two specified indices in the KD-tree data structures.
-    ///
-    /// # Arguments
-    ///
-    /// - `i`: The index of the first element.
-    /// - `j`: The index of the second element.
+    /// - `i`: The absolute index of the first element.
+    /// - `j`: The absolute index of the second element.
     fn swap_item(&mut self, i: usize, j: usize) {
+        let (i, j) = (i - self.base, j - self.base);
+
         self.ids.swap(i, j);
 
         self.coords.swap(2 * i, 2 * j);
         self.coords.swap(2 * i + 1, 2 * j + 1);
     }
-
-    /// Compute the square of the Euclidean distance between two points in a 2D space.
-    ///
-    /// # Arguments
-    ///
-    /// - `ax`: The x-coordinate of the first point.
-    /// - `ay`: The y-coordinate of the first point.
-    /// - `bx`: The x-coordinate of the second point.
-    /// - `by`: The y-coordinate of the second point.
-    ///
-    /// # Returns
-    ///
-    /// The square of the Euclidean distance between the two points.
-    fn sq_dist(ax: f64, ay: f64, bx: f64, by: f64) -> f64 {
-        let dx = ax - bx;
-        let dy = ay - by;
-
-        dx * dx + dy * dy
-    }
 }
 
 #[cfg(test)]
@@ -491,7 +683,7 @@ mod tests {
         [46.0, 78.0],
     ];
 
-    pub const IDS: [usize; 100] = [
+    pub const IDS: [u32; 100] = [
         97, 74, 95, 30, 77, 38, 76, 27, 80, 55, 72, 90, 88, 48, 43, 46, 65, 39, 62, 93, 9, 96, 47,
         8, 3, 12, 15, 14, 21, 41, 36, 40, 69, 56, 85, 78, 17, 71, 44, 19, 18, 13, 99, 24, 67, 33,
         37, 49, 54, 57, 98, 45, 23, 31, 66, 68, 0, 32, 5, 51, 75, 73, 84, 35, 81, 22, 61, 89, 1,
@@ -499,7 +691,7 @@ mod tests {
         50, 7, 28, 82, 70, 29, 34, 91,
     ];
 
-    pub const COORDS: [f64; 200] = [
+    pub const COORDS: [Float; 200] = [
         10.0, 20.0, 6.0, 22.0, 10.0, 10.0, 6.0, 27.0, 20.0, 42.0, 18.0, 28.0, 11.0, 23.0, 13.0,
         25.0, 9.0, 40.0, 26.0, 4.0, 29.0, 50.0, 30.0, 38.0, 41.0, 11.0, 43.0, 12.0, 43.0, 3.0,
         46.0, 12.0, 32.0, 14.0, 35.0, 15.0, 40.0, 31.0, 33.0, 18.0, 43.0, 15.0, 40.0, 34.0, 32.0,
@@ -527,10 +719,10 @@ mod tests {
         index.build_index();
 
         assert_eq!(index.node_size, 10);
-        assert!(!index.points.is_empty());
+        assert_eq!(index.ids.len(), POINTS.len());
 
-        let expected_ids: Vec<usize> = IDS.to_vec();
-        let expected_coords: Vec<f64> = COORDS.to_vec();
+        let expected_ids: Vec<u32> = IDS.to_vec();
+        let expected_coords: Vec<Float> = COORDS.to_vec();
 
         assert_eq!(index.ids, expected_ids);
         assert_eq!(index.coords, expected_coords)
@@ -600,6 +792,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_within_wrapped() {
+        let mut index = KDBush::new(4, 10);
+
+        for (x, y) in [(0.001, 0.5), (0.999, 0.5), (0.5, 0.5), (0.998, 0.6)] {
+            index.add_point(x, y);
+        }
+
+        index.build_index();
+
+        assert_eq!(index.within(0.001, 0.5, 0.01), vec![0]);
+        assert_eq!(index.within_wrapped(0.001, 0.5, 0.01), vec![0, 1]);
+        assert_eq!(index.within_wrapped(0.999, 0.5, 0.01), vec![0, 1]);
+        assert_eq!(index.within_wrapped(0.5, 0.5, 0.6), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_build_large_index() {
+        let mut index = KDBush::new(20000, 64);
+        let mut seed: u64 = 42;
+        let mut random = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        let points: Vec<[f64; 2]> = (0..20000).map(|_| [random(), random()]).collect();
+
+        for point in &points {
+            index.add_point(point[0], point[1]);
+        }
+
+        index.build_index();
+
+        let mut ids = index.ids.clone();
+        ids.sort_unstable();
+
+        assert_eq!(ids, (0..20000).collect::<Vec<u32>>());
+
+        let mut result = index.range(0.2, 0.3, 0.5, 0.7);
+        result.sort_unstable();
+
+        let expected: Vec<usize> = points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p[0] >= 0.2 && p[0] <= 0.5 && p[1] >= 0.3 && p[1] <= 0.7)
+            .map(|(i, _)| i)
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_parallel_sort_matches_serial() {
+        let mut parallel = KDBush::new(20000, 64);
+        let mut seed: u64 = 42;
+        let mut random = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        for _ in 0..20000 {
+            parallel.add_point(random(), random());
+        }
+
+        let mut serial = parallel.clone();
+
+        parallel.build_index();
+
+        serial.ids = (0..20000).collect();
+        serial.partition().sort(0, 19999, 0, 64, usize::MAX);
+
+        assert_eq!(parallel.ids, serial.ids);
+        assert_eq!(parallel.coords, serial.coords);
+    }
+
+    #[test]
+    fn test_empty_index() {
+        let mut index = KDBush::new(0, 10);
+        index.build_index();
+
+        assert!(index.range(0.0, 0.0, 100.0, 100.0).is_empty());
+        assert!(index.within(50.0, 50.0, 20.0).is_empty());
+    }
+
     #[test]
     fn test_sq_dist() {
         let result = KDBush::sq_dist(10.0, 10.0, 5.0, 5.0);
@@ -648,7 +925,7 @@ mod tests {
     #[test]
     fn test_select_large_range() {
         let mut kdbush = KDBush::new(10, 1);
-        kdbush.coords = (0..4000).map(|x| x as f64).collect();
+        kdbush.coords = (0..4000).map(|x| x as Float).collect();
         kdbush.ids = (0..4000).collect();
         kdbush.select(1000, 0, 1999, 0);
 
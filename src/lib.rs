@@ -38,6 +38,18 @@
 //! supercluster = { version = "3.0.4", features = ["log", "serde", "cluster_metadata"] }
 //! ```
 //!
+//! Enable the `rayon` feature to sort the KD-trees and search the cluster neighbors in parallel while loading the points.
+//! The resulting clusters are identical to the ones built sequentially.
+//!
+//! Enable the `f32` feature to store the coordinates of the KD-trees and the cluster positions as `f32`, halving their memory.
+//! The stored coordinates are rounded by at most half a tile unit up to zoom 15 with the default extent, see `Float`.
+//!
+//! Enable the `reader` feature to load a GeoJSON `FeatureCollection` or newline-delimited GeoJSON from a reader, e.g. a file,
+//! with `Supercluster::load_reader`, parsing one feature at a time and keeping only the selected properties.
+//!
+//! Set a `Projection` with `SuperclusterBuilder::projection` to cluster coordinates other than Web Mercator longitudes and latitudes,
+//! e.g. with the built-in `PlateCarree` projection or a polar stereographic or floor plan projection of your own.
+//!
 //! Below is an example of how to create and run a supercluster using the crate.
 //!
 //! This example demonstrates how to build supercluster options, create a new supercluster, and get a tile.
@@ -53,7 +65,7 @@
 //!         .min_points(2)
 //!         .max_zoom(16)
 //!         .coordinate_system(CoordinateSystem::LatLng)
-//!         .build();
+//!         .build()?;
 //!
 //!     // Create a new instance with the specified configuration settings
 //!     let mut cluster = Supercluster::new(options);
@@ -64,7 +76,7 @@
 //!         .build();
 //!
 //!     // Load a list of features into the supercluster
-//!     let index = cluster.load(features)?;
+//!     let (index, _) = cluster.load(features)?;
 //!
 //!     if let Err(err) = index.get_tile(0, 0.0, 0.0) {
 //!        println!("Error: {}", err);
@@ -108,24 +120,66 @@
 /// This module contains the builder pattern for the supercluster configuration settings.
 pub mod builder;
 
+/// Callback module.
+/// This module contains the shareable wrapper for user-provided functions in the configuration settings.
+pub mod callback;
+
+/// Data module.
+/// This module contains the typed columns storing the points and clusters of the zoom levels.
+pub mod data;
+
 /// Supercluster error module.
 /// This module contains the error types for the supercluster crate.
 pub mod error;
 
+/// Filter module.
+/// This module contains the cache of the filtered cluster hierarchies.
+pub mod filter;
+
+/// Geometry module.
+/// This module contains the handling of the input geometries that are not points.
+pub mod geometry;
+
 /// KDBush module.
 /// This module contains the KDBush implementation for the supercluster crate.
 pub mod kdbush;
 
+/// Point module.
+/// This module contains the trait of the input points and the clusters returned from the queries.
+pub mod point;
+
+/// Projection module.
+/// This module contains the trait of the custom projections into the clustering space.
+pub mod projection;
+
 /// Range module.
 /// This module contains the range implementation for the supercluster crate.
 pub mod range;
 
+/// Reader module.
+/// This module contains the streaming parser of the GeoJSON inputs.
+#[cfg(feature = "reader")]
+pub mod reader;
+
 /// Supercluster module.
 /// This module contains the supercluster implementation for the supercluster crate.
 pub mod supercluster;
 
+/// Validation module.
+/// This module contains the validation of the input points and the report of the skipped points.
+pub mod validation;
+
 pub use builder::*;
+pub use callback::*;
+pub use data::*;
 pub use error::*;
+pub use filter::*;
+pub use geometry::*;
 pub use kdbush::*;
+pub use point::*;
+pub use projection::*;
 pub use range::*;
+#[cfg(feature = "reader")]
+pub use reader::*;
 pub use supercluster::*;
+pub use validation::*;
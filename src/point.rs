@@ -0,0 +1,378 @@
+//! # Point module
+//!
+//! This module contains the trait of the input points, which decouples the clustering from GeoJSON features,
+//! and the clusters returned from the queries along with the input points.
+//! The `ClusterItem` enum tells the clusters and the points apart without reading the GeoJSON properties.
+
+use geojson::{feature::Id, Feature, Geometry, JsonObject, Value};
+
+use crate::ClusterId;
+
+/// An input point of the clustering.
+/// GeoJSON features implement the trait, and other types implement it to be clustered without converting them into GeoJSON.
+pub trait ClusterPoint {
+    /// Typed item returned from the queries, representing either an input point or a cluster.
+    type Payload;
+
+    /// Get the coordinates at which the point is clustered.
+    ///
+    /// # Returns
+    ///
+    /// The x and y coordinates, or the longitude and latitude, otherwise `None` to leave the point out of the clustering.
+    fn coords(&self) -> Option<[f64; 2]>;
+
+    /// Get the item returned from the queries for the point.
+    ///
+    /// # Returns
+    ///
+    /// The typed payload of the point.
+    fn payload(&self) -> Self::Payload;
+
+    /// Get the item returned from the queries for a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster`: The cluster.
+    ///
+    /// # Returns
+    ///
+    /// The typed payload of the cluster.
+    fn cluster_payload(cluster: Cluster) -> Self::Payload;
+
+    /// Get the item returned from the queries for one of the points a multi point is expanded into.
+    ///
+    /// # Arguments
+    ///
+    /// - `part`: The index of the point within the multi point.
+    ///
+    /// # Returns
+    ///
+    /// The typed payload of the point, by default the payload of the whole multi point.
+    fn part_payload(&self, part: usize) -> Self::Payload {
+        let _ = part;
+        self.payload()
+    }
+
+    /// Get the weight of the point, used when the weight option does not apply.
+    ///
+    /// # Returns
+    ///
+    /// The weight of the point, by default 1.0.
+    fn weight(&self) -> f64 {
+        1.0
+    }
+
+    /// Get the partition key of the point, used when the partition option does not apply.
+    ///
+    /// # Returns
+    ///
+    /// The partition key of the point, by default `None`.
+    fn partition(&self) -> Option<String> {
+        None
+    }
+
+    /// Get the properties of the point aggregated into the clusters with the `map` and `reduce` options.
+    ///
+    /// # Returns
+    ///
+    /// The properties of the point, by default the properties of its GeoJSON feature.
+    fn properties(&self) -> Option<&JsonObject> {
+        self.as_feature()
+            .and_then(|feature| feature.properties.as_ref())
+    }
+
+    /// Get the GeoJSON feature of the point, used by the options reading features, e.g. `weight_property`,
+    /// `partition_property` and `geometry_mode`, and to find points by GeoJSON ID.
+    ///
+    /// # Returns
+    ///
+    /// The GeoJSON feature of the point, by default `None`.
+    fn as_feature(&self) -> Option<&Feature> {
+        None
+    }
+}
+
+/// A cluster returned from the queries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cluster {
+    /// The internal ID of the cluster, used to query its children, leaves and expansion zoom.
+    pub id: ClusterId,
+
+    /// The ID of the cluster feature, which is the internal ID or the hexadecimal stable ID depending on the ID mode.
+    pub feature_id: Id,
+
+    /// The number of points in the cluster.
+    pub count: usize,
+
+    /// The total weight of the points in the cluster.
+    pub weight: f64,
+
+    /// The coordinates at which the cluster is displayed.
+    pub coords: [f64; 2],
+
+    /// The bounding box of the points in the cluster as [min_x, min_y, max_x, max_y].
+    /// Only available with the `cluster_bbox` option.
+    pub bbox: Option<[f64; 4]>,
+
+    /// The partition key of the points in the cluster.
+    pub partition: Option<String>,
+
+    /// The GeoJSON properties of the cluster, with the aggregated properties.
+    /// Only available with the `cluster_metadata` feature.
+    pub properties: Option<JsonObject>,
+}
+
+impl From<Cluster> for Feature {
+    fn from(cluster: Cluster) -> Self {
+        Feature {
+            id: Some(cluster.feature_id),
+            bbox: cluster.bbox.map(|bbox| bbox.to_vec()),
+            geometry: Some(Geometry::new(Value::Point(cluster.coords.to_vec()))),
+            properties: cluster.properties,
+            foreign_members: None,
+        }
+    }
+}
+
+/// A typed point or cluster returned from the queries.
+/// Converting the item into a GeoJSON feature is a separate step, see `ClusterItem::into_payload`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClusterItem<T = Feature> {
+    /// A cluster of points.
+    Cluster(Cluster),
+
+    /// An unclustered input point.
+    Point {
+        /// The index of the point in the loaded input points.
+        index: usize,
+
+        /// The index of the point within a multi point, otherwise `None`.
+        part: Option<usize>,
+
+        /// The input point.
+        point: T,
+    },
+}
+
+impl<T> ClusterItem<T> {
+    /// Check whether the item is a cluster.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the item is a cluster, otherwise `false`.
+    pub fn is_cluster(&self) -> bool {
+        matches!(self, ClusterItem::Cluster(_))
+    }
+
+    /// Get the number of points represented by the item.
+    ///
+    /// # Returns
+    ///
+    /// The number of points in the cluster, otherwise 1 for a point.
+    pub fn count(&self) -> usize {
+        match self {
+            ClusterItem::Cluster(cluster) => cluster.count,
+            ClusterItem::Point { .. } => 1,
+        }
+    }
+}
+
+impl<T: ClusterPoint> ClusterItem<T> {
+    /// Convert the item into the payload of the input type, e.g. a GeoJSON feature.
+    ///
+    /// # Returns
+    ///
+    /// The payload of the cluster, or of the point.
+    pub fn into_payload(self) -> T::Payload {
+        match self {
+            ClusterItem::Cluster(cluster) => T::cluster_payload(cluster),
+            ClusterItem::Point {
+                part: Some(part),
+                point,
+                ..
+            } => point.part_payload(part),
+            ClusterItem::Point { point, .. } => point.payload(),
+        }
+    }
+}
+
+impl<T: Clone> ClusterItem<&T> {
+    /// Clone the point referenced by the item.
+    ///
+    /// # Returns
+    ///
+    /// The item owning a clone of the point.
+    pub fn cloned(self) -> ClusterItem<T> {
+        match self {
+            ClusterItem::Cluster(cluster) => ClusterItem::Cluster(cluster),
+            ClusterItem::Point { index, part, point } => ClusterItem::Point {
+                index,
+                part,
+                point: point.clone(),
+            },
+        }
+    }
+}
+
+impl<T: ClusterPoint<Payload = Feature>> From<ClusterItem<T>> for Feature {
+    fn from(item: ClusterItem<T>) -> Self {
+        item.into_payload()
+    }
+}
+
+impl<T: ClusterPoint + ?Sized> ClusterPoint for &T {
+    type Payload = T::Payload;
+
+    fn coords(&self) -> Option<[f64; 2]> {
+        (**self).coords()
+    }
+
+    fn payload(&self) -> Self::Payload {
+        (**self).payload()
+    }
+
+    fn cluster_payload(cluster: Cluster) -> Self::Payload {
+        T::cluster_payload(cluster)
+    }
+
+    fn part_payload(&self, part: usize) -> Self::Payload {
+        (**self).part_payload(part)
+    }
+
+    fn weight(&self) -> f64 {
+        (**self).weight()
+    }
+
+    fn partition(&self) -> Option<String> {
+        (**self).partition()
+    }
+
+    fn properties(&self) -> Option<&JsonObject> {
+        (**self).properties()
+    }
+
+    fn as_feature(&self) -> Option<&Feature> {
+        (**self).as_feature()
+    }
+}
+
+impl ClusterPoint for Feature {
+    type Payload = Feature;
+
+    fn coords(&self) -> Option<[f64; 2]> {
+        match self.geometry.as_ref().map(|geometry| &geometry.value) {
+            Some(Value::Point(coords)) if coords.len() >= 2 => Some([coords[0], coords[1]]),
+            _ => None,
+        }
+    }
+
+    fn payload(&self) -> Feature {
+        self.to_owned()
+    }
+
+    fn cluster_payload(cluster: Cluster) -> Feature {
+        cluster.into()
+    }
+
+    fn part_payload(&self, part: usize) -> Feature {
+        let geometry = match self.geometry.as_ref().map(|geometry| &geometry.value) {
+            Some(Value::MultiPoint(points)) => points
+                .get(part)
+                .map(|coords| Geometry::new(Value::Point(coords.to_owned()))),
+            _ => None,
+        };
+
+        // The point keeps the ID and properties of the multi point
+        Feature {
+            id: self.id.to_owned(),
+            bbox: None,
+            geometry,
+            properties: self.properties.to_owned(),
+            foreign_members: self.foreign_members.to_owned(),
+        }
+    }
+
+    fn as_feature(&self) -> Option<&Feature> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_cluster_point() {
+        let mut feature = Feature {
+            geometry: Some(Geometry::new(Value::MultiPoint(vec![
+                vec![1.0, 2.0],
+                vec![3.0, 4.0],
+            ]))),
+            ..Default::default()
+        };
+        feature.set_property("name", "site");
+
+        assert_eq!(feature.coords(), None);
+        assert_eq!(feature.payload(), feature);
+        assert_eq!(
+            feature.part_payload(1).geometry,
+            Some(Geometry::new(Value::Point(vec![3.0, 4.0])))
+        );
+        assert_eq!(feature.part_payload(1).properties, feature.properties);
+
+        let cluster = Cluster {
+            id: ClusterId(164),
+            feature_id: Id::String("164".to_string()),
+            count: 2,
+            weight: 2.0,
+            coords: [1.0, 2.0],
+            bbox: None,
+            partition: None,
+            properties: None,
+        };
+        let feature = Feature::cluster_payload(cluster);
+
+        assert_eq!(feature.id, Some(Id::String("164".to_string())));
+        assert_eq!(feature.coords(), Some([1.0, 2.0]));
+    }
+
+    #[test]
+    fn test_cluster_item() {
+        let mut point = Feature {
+            geometry: Some(Geometry::new(Value::MultiPoint(vec![
+                vec![1.0, 2.0],
+                vec![3.0, 4.0],
+            ]))),
+            ..Default::default()
+        };
+        point.set_property("name", "site");
+
+        let item = ClusterItem::Point {
+            index: 0,
+            part: Some(0),
+            point,
+        };
+
+        assert!(!item.is_cluster());
+        assert_eq!(item.count(), 1);
+        assert_eq!(
+            Feature::from(item).geometry,
+            Some(Geometry::new(Value::Point(vec![1.0, 2.0])))
+        );
+
+        let item: ClusterItem = ClusterItem::Cluster(Cluster {
+            id: ClusterId(164),
+            feature_id: Id::String("164".to_string()),
+            count: 2,
+            weight: 2.0,
+            coords: [1.0, 2.0],
+            bbox: Some([1.0, 2.0, 1.0, 2.0]),
+            partition: None,
+            properties: None,
+        });
+
+        assert!(item.is_cluster());
+        assert_eq!(item.count(), 2);
+        assert_eq!(item.into_payload().bbox, Some(vec![1.0, 2.0, 1.0, 2.0]));
+    }
+}
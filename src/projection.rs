@@ -0,0 +1,175 @@
+//! # Projection module
+//!
+//! Contains the `Projection` trait used to cluster points in a custom coordinate system,
+//! and the `PlateCarree` projection of longitudes and latitudes.
+
+/// Number of points sampled along each edge of a bounding box to project it.
+const EDGE_SAMPLES: usize = 16;
+
+/// Projection of input coordinates into the unit square in which the points are clustered.
+/// The x axis of the unit square points east and the y axis points south, as in the tiles.
+///
+/// Used with `CoordinateSystem::Custom`, e.g. for plate carrée, polar stereographic or floor plan coordinates.
+pub trait Projection: Send + Sync {
+    /// Project input coordinates into the unit square.
+    ///
+    /// # Arguments
+    ///
+    /// - `coords`: The input coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The x and y coordinates within the unit square.
+    fn forward(&self, coords: [f64; 2]) -> [f64; 2];
+
+    /// Convert coordinates of the unit square back into input coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The x and y coordinates within the unit square.
+    ///
+    /// # Returns
+    ///
+    /// The input coordinates.
+    fn inverse(&self, point: [f64; 2]) -> [f64; 2];
+
+    /// Get the range of input x coordinates wrapping around the unit square, e.g. `[-180.0, 180.0]` for longitudes.
+    /// Queries crossing the edges of a wrapping range are split at the edges, and the tiles at the left and right
+    /// edges of the unit square include the points across the edge.
+    ///
+    /// # Returns
+    ///
+    /// The western and eastern bounds of the input x coordinates, otherwise `None` if the projection does not wrap.
+    fn wrap(&self) -> Option<[f64; 2]> {
+        None
+    }
+
+    /// Project a bounding box of input coordinates into the unit square.
+    /// The default implementation samples points along the edges of the bounding box,
+    /// which encloses the projected box for any continuous projection.
+    ///
+    /// # Arguments
+    ///
+    /// - `bbox`: The bounding box of input coordinates as `[min_x, min_y, max_x, max_y]`.
+    ///
+    /// # Returns
+    ///
+    /// The bounding box within the unit square as `[min_x, min_y, max_x, max_y]`.
+    fn forward_bbox(&self, bbox: [f64; 4]) -> [f64; 4] {
+        get_edge_bbox(bbox, |coords| self.forward(coords))
+    }
+
+    /// Convert a bounding box of the unit square back into input coordinates.
+    /// The default implementation samples points along the edges of the bounding box.
+    ///
+    /// # Arguments
+    ///
+    /// - `bbox`: The bounding box within the unit square as `[min_x, min_y, max_x, max_y]`.
+    ///
+    /// # Returns
+    ///
+    /// The bounding box of input coordinates as `[min_x, min_y, max_x, max_y]`.
+    fn inverse_bbox(&self, bbox: [f64; 4]) -> [f64; 4] {
+        get_edge_bbox(bbox, |point| self.inverse(point))
+    }
+}
+
+/// Equirectangular projection of longitudes and latitudes (EPSG:4326).
+/// Longitudes span the unit square and latitudes span its middle half, so distances keep the same scale on both axes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlateCarree;
+
+impl Projection for PlateCarree {
+    fn forward(&self, coords: [f64; 2]) -> [f64; 2] {
+        [coords[0] / 360.0 + 0.5, 0.5 - coords[1] / 360.0]
+    }
+
+    fn inverse(&self, point: [f64; 2]) -> [f64; 2] {
+        [(point[0] - 0.5) * 360.0, (0.5 - point[1]) * 360.0]
+    }
+
+    fn wrap(&self) -> Option<[f64; 2]> {
+        Some([-180.0, 180.0])
+    }
+}
+
+/// Map the edges of a bounding box and compute the bounding box of the mapped points.
+/// Points mapped to non-finite coordinates are ignored.
+///
+/// # Arguments
+///
+/// - `bbox`: The bounding box as `[min_x, min_y, max_x, max_y]`.
+/// - `map`: The function mapping the points.
+///
+/// # Returns
+///
+/// The bounding box of the mapped points as `[min_x, min_y, max_x, max_y]`.
+fn get_edge_bbox<F: Fn([f64; 2]) -> [f64; 2]>(bbox: [f64; 4], map: F) -> [f64; 4] {
+    let [min_x, min_y, max_x, max_y] = bbox;
+    let mut result = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+
+    for i in 0..=EDGE_SAMPLES {
+        let t = i as f64 / EDGE_SAMPLES as f64;
+        let x = min_x + (max_x - min_x) * t;
+        let y = min_y + (max_y - min_y) * t;
+
+        for [px, py] in [
+            map([x, min_y]),
+            map([x, max_y]),
+            map([min_x, y]),
+            map([max_x, y]),
+        ] {
+            if px.is_finite() && py.is_finite() {
+                result = [
+                    result[0].min(px),
+                    result[1].min(py),
+                    result[2].max(px),
+                    result[3].max(py),
+                ];
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plate_carree() {
+        assert_eq!(PlateCarree.forward([0.0, 0.0]), [0.5, 0.5]);
+        assert_eq!(PlateCarree.forward([-180.0, 90.0]), [0.0, 0.25]);
+        assert_eq!(PlateCarree.forward([180.0, -90.0]), [1.0, 0.75]);
+        assert_eq!(PlateCarree.inverse([0.75, 0.375]), [90.0, 45.0]);
+        assert_eq!(
+            PlateCarree.forward_bbox([-90.0, -45.0, 90.0, 45.0]),
+            [0.25, 0.375, 0.75, 0.625]
+        );
+        assert_eq!(
+            PlateCarree.inverse_bbox([0.25, 0.375, 0.75, 0.625]),
+            [-90.0, -45.0, 90.0, 45.0]
+        );
+    }
+
+    #[test]
+    fn test_edge_bbox() {
+        // The corners of the square are mapped onto the axes
+        let rotate = |[x, y]: [f64; 2]| [x - y, x + y];
+
+        assert_eq!(
+            get_edge_bbox([-1.0, -1.0, 1.0, 1.0], rotate),
+            [-2.0, -2.0, 2.0, 2.0]
+        );
+        assert_eq!(
+            get_edge_bbox([0.0, 0.0, 1.0, 1.0], |[x, y]| [1.0 / x, y]),
+            [1.0, 0.0, 16.0, 1.0]
+        );
+    }
+}
@@ -5,6 +5,23 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Scaling of the x and y axes of a data range into the unit square.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum AxisScaling {
+    /// A single offset and scale for both axes, from the lowest minimum and the highest maximum of the axes.
+    #[default]
+    Shared,
+
+    /// An offset per axis and the scale of the longer axis, so the aspect ratio is preserved
+    /// and the shorter axis is centered in the unit square.
+    PreserveAspect,
+
+    /// An offset and a scale per axis, so both axes span the unit square.
+    /// The cluster radius then covers a different distance along each axis.
+    Stretch,
+}
+
 /// The range of the incoming data if choosing the cartesian coordinate system.
 /// Applicable for non-geospatial data (i.e. microscopy, etc.).
 #[derive(Clone, Debug, PartialEq)]
@@ -33,6 +50,26 @@ pub struct DataRange {
     /// The cached value for scale.
     /// No default value.
     pub scale: Option<f64>,
+
+    /// The cached value for the offset of the y axis, unless the axes are scaled together.
+    /// No default value.
+    pub offset_y: Option<f64>,
+
+    /// The cached value for the scale of the y axis, unless the axes are scaled together.
+    /// No default value.
+    pub scale_y: Option<f64>,
+
+    /// The scaling of the x and y axes into the unit square.
+    /// Default is `AxisScaling::Shared`.
+    pub scaling: AxisScaling,
+
+    /// Whether the range is computed from the points in `Supercluster::load`, replacing the bounds and the cached values.
+    /// Default is false.
+    pub auto: bool,
+
+    /// The padding added on each side of a computed range, as a fraction of its size.
+    /// Default is 0.0.
+    pub padding: f64,
 }
 
 impl DataRange {
@@ -64,6 +101,134 @@ impl DataRange {
         v_scaled * self.scale() + self.offset()
     }
 
+    /// Normalize a point to the unit square, scaling each axis according to the axis scaling.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The x and y coordinates to be normalized.
+    ///
+    /// # Returns
+    ///
+    /// The normalized x and y coordinates.
+    pub fn normalize_point(&self, point: [f64; 2]) -> [f64; 2] {
+        [0, 1].map(|axis| {
+            let (offset, scale) = self.transform(axis);
+
+            (point[axis] - offset) / scale
+        })
+    }
+
+    /// Denormalize a point from the unit square to the original range.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The normalized x and y coordinates to be denormalized.
+    ///
+    /// # Returns
+    ///
+    /// The denormalized x and y coordinates.
+    pub fn denormalize_point(&self, point: [f64; 2]) -> [f64; 2] {
+        [0, 1].map(|axis| {
+            let (offset, scale) = self.transform(axis);
+
+            point[axis] * scale + offset
+        })
+    }
+
+    /// Check whether a point falls within the range.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The x and y coordinates to be checked.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the normalized coordinates are within [0, 1], otherwise `false`.
+    pub fn contains(&self, point: [f64; 2]) -> bool {
+        self.normalize_point(point)
+            .iter()
+            .all(|v| (0.0..=1.0).contains(v))
+    }
+
+    /// Clamp a point into the range.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The x and y coordinates to be clamped.
+    ///
+    /// # Returns
+    ///
+    /// The nearest coordinates whose normalized coordinates are within [0, 1].
+    pub fn clamp_point(&self, point: [f64; 2]) -> [f64; 2] {
+        let [x, y] = self.normalize_point(point);
+
+        self.denormalize_point([x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)])
+    }
+
+    /// Fit the range to the bounds of the loaded points and cache the offset and scale of its axes.
+    /// Each axis is padded on both sides by the `padding` fraction of its scale.
+    ///
+    /// # Arguments
+    ///
+    /// - `bounds`: The bounds of the points as `[min_x, min_y, max_x, max_y]`.
+    pub fn fit(&mut self, bounds: [f64; 4]) {
+        [self.min_x, self.min_y, self.max_x, self.max_y] = bounds;
+        self.offset = None;
+        self.scale = None;
+        self.offset_y = None;
+        self.scale_y = None;
+
+        let padding = self.padding.max(0.0);
+        let [(offset, scale), (offset_y, scale_y)] = [0, 1].map(|axis| {
+            let (offset, scale) = self.transform(axis);
+
+            // A single point is placed at the origin of a unit range
+            if scale > 0.0 {
+                (offset - scale * padding, scale * (1.0 + 2.0 * padding))
+            } else {
+                (offset, 1.0)
+            }
+        });
+
+        self.offset = Some(offset);
+        self.scale = Some(scale);
+        self.offset_y = Some(offset_y);
+        self.scale_y = Some(scale_y);
+    }
+
+    /// Get the offset and scale of an axis according to the axis scaling.
+    /// The cached values take precedence over the values computed from the bounds.
+    ///
+    /// # Arguments
+    ///
+    /// - `axis`: The axis, 0 for x and 1 for y.
+    ///
+    /// # Returns
+    ///
+    /// The offset and scale of the axis.
+    fn transform(&self, axis: usize) -> (f64, f64) {
+        let min = [self.min_x, self.min_y][axis];
+        let size = [self.max_x - self.min_x, self.max_y - self.min_y];
+
+        let (offset, scale) = match self.scaling {
+            AxisScaling::Shared => return (self.offset(), self.scale()),
+            AxisScaling::PreserveAspect => {
+                let scale = f64::max(size[0], size[1]);
+
+                (min - (scale - size[axis]) / 2.0, scale)
+            }
+            AxisScaling::Stretch => (min, size[axis]),
+        };
+
+        match axis {
+            0 => (self.offset.unwrap_or(offset), self.scale.unwrap_or(scale)),
+            _ => (
+                self.offset_y.unwrap_or(offset),
+                self.scale_y.unwrap_or(scale),
+            ),
+        }
+    }
+
     /// Compute and cache the minimum range value.
     /// If `offset` is not set, the minimum value between `min_x` and `min_y` is returned.
     ///
@@ -101,6 +266,11 @@ impl Default for DataRange {
             max_y: 1.0,
             offset: None,
             scale: None,
+            offset_y: None,
+            scale_y: None,
+            scaling: AxisScaling::Shared,
+            auto: false,
+            padding: 0.0,
         }
     }
 }
@@ -117,6 +287,41 @@ mod tests {
         assert_eq!(data_range.min_y, 0.0);
         assert_eq!(data_range.max_x, 1.0);
         assert_eq!(data_range.max_y, 1.0);
+        assert!(!data_range.auto);
+        assert_eq!(data_range.padding, 0.0);
+        assert_eq!(data_range.scaling, AxisScaling::Shared);
+    }
+
+    #[test]
+    fn test_data_range_scaling() {
+        let mut data_range = DataRange {
+            min_x: 0.0,
+            min_y: 5000.0,
+            max_x: 10000.0,
+            max_y: 5200.0,
+            ..Default::default()
+        };
+
+        assert_eq!(data_range.normalize_point([0.0, 5000.0]), [0.0, 0.5]);
+        assert_eq!(data_range.normalize_point([10000.0, 5200.0]), [1.0, 0.52]);
+
+        data_range.scaling = AxisScaling::PreserveAspect;
+
+        assert_eq!(data_range.normalize_point([0.0, 5000.0]), [0.0, 0.49]);
+        assert_eq!(data_range.normalize_point([5000.0, 5100.0]), [0.5, 0.5]);
+        assert_eq!(data_range.denormalize_point([1.0, 0.51]), [10000.0, 5200.0]);
+
+        data_range.scaling = AxisScaling::Stretch;
+
+        assert_eq!(data_range.normalize_point([0.0, 5000.0]), [0.0, 0.0]);
+        assert_eq!(data_range.normalize_point([5000.0, 5100.0]), [0.5, 0.5]);
+        assert_eq!(data_range.denormalize_point([1.0, 1.0]), [10000.0, 5200.0]);
+
+        data_range.padding = 0.5;
+        data_range.fit([0.0, 0.0, 100.0, 10.0]);
+
+        assert_eq!(data_range.normalize_point([-50.0, -5.0]), [0.0, 0.0]);
+        assert_eq!(data_range.normalize_point([150.0, 15.0]), [1.0, 1.0]);
     }
 
     #[test]
@@ -136,5 +341,31 @@ mod tests {
         assert_eq!(data_range.denormalize(0.0), -20.0);
         assert_eq!(data_range.denormalize(0.5), 40.0);
         assert_eq!(data_range.denormalize(1.0), 100.0);
+
+        assert_eq!(data_range.clamp_point([40.0, 10.0]), [40.0, 10.0]);
+        assert_eq!(data_range.clamp_point([-50.0, 120.0]), [-20.0, 100.0]);
+    }
+
+    #[test]
+    fn test_data_range_fit() {
+        let mut data_range = DataRange {
+            auto: true,
+            padding: 0.1,
+            ..Default::default()
+        };
+
+        data_range.fit([-10.0, 0.0, 90.0, 50.0]);
+
+        assert_eq!(data_range.min_x, -10.0);
+        assert_eq!(data_range.max_y, 50.0);
+        assert_eq!(data_range.normalize(-20.0), 0.0);
+        assert_eq!(data_range.normalize(40.0), 0.5);
+        assert_eq!(data_range.denormalize(1.0), 100.0);
+        assert!(data_range.contains([95.0, 95.0]));
+        assert!(!data_range.contains([101.0, 0.0]));
+
+        data_range.fit([5.0, 5.0, 5.0, 5.0]);
+
+        assert_eq!(data_range.normalize(5.0), 0.0);
     }
 }
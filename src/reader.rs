@@ -0,0 +1,279 @@
+//! # Reader module
+//!
+//! Contains the streaming parser of the GeoJSON inputs loaded with `Supercluster::load_reader`.
+//! The features are parsed one at a time, so the input is never held as a whole `FeatureCollection`,
+//! and only the selected properties of each feature are kept.
+
+use std::{
+    fmt,
+    io::{BufRead, BufReader, Read},
+};
+
+use geojson::Feature;
+use serde::{
+    de::{DeserializeSeed, Error, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserializer,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::SuperclusterError;
+
+/// Format of a GeoJSON input.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum GeoJsonFormat {
+    /// A single GeoJSON `FeatureCollection` object.
+    #[default]
+    FeatureCollection,
+
+    /// Newline-delimited GeoJSON, with one feature per line. Blank lines are skipped.
+    NewlineDelimited,
+}
+
+/// Read the features of a GeoJSON input one at a time.
+///
+/// # Arguments
+///
+/// - `reader`: The reader of the GeoJSON input, e.g. a file.
+/// - `format`: The format of the GeoJSON input.
+/// - `properties`: The names of the properties kept on the features, otherwise `None` to keep all of them.
+/// - `on_feature`: The function called with each feature, in the order of the input.
+///
+/// # Returns
+///
+/// Nothing if the input was read, otherwise an error if it could not be read or parsed.
+pub fn read_features<R, F>(
+    reader: R,
+    format: GeoJsonFormat,
+    properties: Option<&[&str]>,
+    mut on_feature: F,
+) -> Result<(), SuperclusterError>
+where
+    R: Read,
+    F: FnMut(Feature),
+{
+    let mut on_feature = |mut feature: Feature| {
+        if let (Some(selected), Some(feature_properties)) = (properties, &mut feature.properties) {
+            feature_properties.retain(|key, _| selected.contains(&key.as_str()));
+        }
+
+        on_feature(feature);
+    };
+
+    match format {
+        GeoJsonFormat::FeatureCollection => {
+            let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+
+            FeatureCollectionSeed {
+                on_feature: &mut on_feature,
+            }
+            .deserialize(&mut deserializer)
+            .and_then(|_| deserializer.end())
+            .map_err(get_reader_error)
+        }
+        GeoJsonFormat::NewlineDelimited => {
+            for (i, line) in BufReader::new(reader).lines().enumerate() {
+                let line = line.map_err(|err| SuperclusterError::Io(err.to_string()))?;
+
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let feature = serde_json::from_str(&line)
+                    .map_err(|err| SuperclusterError::Parse(format!("line {}: {}", i + 1, err)))?;
+
+                on_feature(feature);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Convert a JSON error into a supercluster error.
+///
+/// # Arguments
+///
+/// - `err`: The JSON error.
+///
+/// # Returns
+///
+/// An I/O error if the input could not be read, otherwise a parse error.
+fn get_reader_error(err: serde_json::Error) -> SuperclusterError {
+    if err.is_io() {
+        SuperclusterError::Io(err.to_string())
+    } else {
+        SuperclusterError::Parse(err.to_string())
+    }
+}
+
+/// Deserializer of a `FeatureCollection` object, passing its features on instead of collecting them.
+struct FeatureCollectionSeed<'a, F> {
+    /// The function called with each feature.
+    on_feature: &'a mut F,
+}
+
+impl<'de, F: FnMut(Feature)> DeserializeSeed<'de> for FeatureCollectionSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(Feature)> Visitor<'de> for FeatureCollectionSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a GeoJSON FeatureCollection object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut has_features = false;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "type" => {
+                    let kind = map.next_value::<String>()?;
+
+                    if kind != "FeatureCollection" {
+                        return Err(A::Error::custom(format!(
+                            "expected a FeatureCollection, found {}",
+                            kind
+                        )));
+                    }
+                }
+                "features" => {
+                    map.next_value_seed(FeaturesSeed {
+                        on_feature: &mut *self.on_feature,
+                    })?;
+
+                    has_features = true;
+                }
+                // Skip the bounding box and the foreign members
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        if !has_features {
+            return Err(A::Error::missing_field("features"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Deserializer of the `features` array of a `FeatureCollection` object, parsing one feature at a time.
+struct FeaturesSeed<'a, F> {
+    /// The function called with each feature.
+    on_feature: &'a mut F,
+}
+
+impl<'de, F: FnMut(Feature)> DeserializeSeed<'de> for FeaturesSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(Feature)> Visitor<'de> for FeaturesSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of GeoJSON features")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(feature) = seq.next_element::<Feature>()? {
+            (self.on_feature)(feature);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEATURE_COLLECTION: &str = r#"{
+        "type": "FeatureCollection",
+        "bbox": [0, 0, 1, 1],
+        "features": [
+            {"type": "Feature", "geometry": {"type": "Point", "coordinates": [0, 0]}, "properties": {"name": "a", "size": 1}},
+            {"type": "Feature", "geometry": {"type": "Point", "coordinates": [1, 1]}, "properties": {"name": "b", "size": 2}}
+        ]
+    }"#;
+
+    fn read(
+        input: &str,
+        format: GeoJsonFormat,
+        properties: Option<&[&str]>,
+    ) -> Result<Vec<Feature>, SuperclusterError> {
+        let mut features = vec![];
+
+        read_features(input.as_bytes(), format, properties, |feature| {
+            features.push(feature)
+        })?;
+
+        Ok(features)
+    }
+
+    #[test]
+    fn test_read_feature_collection() {
+        let features = read(FEATURE_COLLECTION, GeoJsonFormat::FeatureCollection, None).unwrap();
+
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[1].property("name").unwrap(), "b");
+        assert_eq!(features[1].property("size").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_read_newline_delimited() {
+        let input = concat!(
+            r#"{"type": "Feature", "geometry": {"type": "Point", "coordinates": [0, 0]}, "properties": {"name": "a", "size": 1}}"#,
+            "\n\n",
+            r#"{"type": "Feature", "geometry": {"type": "Point", "coordinates": [1, 1]}, "properties": {"name": "b", "size": 2}}"#,
+            "\n",
+        );
+        let features = read(input, GeoJsonFormat::NewlineDelimited, Some(&["name"])).unwrap();
+
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0].property("name").unwrap(), "a");
+        assert!(!features[0].contains_property("size"));
+    }
+
+    #[test]
+    fn test_read_errors() {
+        assert!(matches!(
+            read("[]", GeoJsonFormat::FeatureCollection, None),
+            Err(SuperclusterError::Parse(_))
+        ));
+        assert!(matches!(
+            read(
+                r#"{"type": "Feature", "properties": {}}"#,
+                GeoJsonFormat::FeatureCollection,
+                None
+            ),
+            Err(SuperclusterError::Parse(_))
+        ));
+        assert!(matches!(
+            read(
+                r#"{"type": "FeatureCollection"}"#,
+                GeoJsonFormat::FeatureCollection,
+                None
+            ),
+            Err(SuperclusterError::Parse(_))
+        ));
+        assert_eq!(
+            read("\n{", GeoJsonFormat::NewlineDelimited, None)
+                .unwrap_err()
+                .to_string(),
+            "Failed to parse the input: line 2: EOF while parsing an object at line 1 column 1"
+        );
+    }
+}
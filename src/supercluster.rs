@@ -9,14 +9,37 @@
 //!
 //! The module also contains the `CoordinateSystem` enum, which defines the coordinate system for clustering.
 //!
-//! The `CoordinateSystem` enum has two variants: `LatLng` for latitude and longitude coordinates and
-//! `Cartesian` for Cartesian coordinates.
-
-use std::{collections::HashMap, f64::consts::PI, hash::BuildHasherDefault};
+//! The `CoordinateSystem` enum has three variants: `LatLng` for latitude and longitude coordinates,
+//! `Cartesian` for Cartesian coordinates and `Custom` for coordinates converted by a user-provided `Projection`.
+//!
+//! The `ClusterAlgorithm` enum selects between the greedy radius clustering and the grid clustering,
+//! and the `ClusterPosition` enum selects where the clusters are displayed.
+//! The `ClusterIdMode` enum selects how the clusters are identified in the output features,
+//! and the `RadiusUnit` enum selects whether the cluster radius is in pixels or in meters.
+//!
+//! The `Supercluster` struct is generic over its input points, which implement the `ClusterPoint` trait.
+//! GeoJSON features are the default input points, and the queries return the typed payload of the input type.
+
+#[cfg(feature = "reader")]
+use std::io::Read;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    f64::consts::{PI, SQRT_2},
+    hash::BuildHasherDefault,
+    ops::Range,
+    sync::Arc,
+};
 
 #[cfg(feature = "cluster_metadata")]
 use geojson::JsonObject;
-use geojson::{feature::Id, Feature, FeatureCollection, Geometry, Value::Point};
+use geojson::{
+    feature::Id,
+    Feature, FeatureCollection, Geometry, Value,
+    Value::{LineString, MultiPoint, Point, Polygon},
+};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "cluster_metadata")]
@@ -24,24 +47,17 @@ use serde_json::json;
 use twox_hash::XxHash64;
 
 use crate::{
-    DataRange, FeatureBuilder, KDBush, SuperclusterBuilder, SuperclusterError, SuperclusterOptions,
+    geometry::{get_positions, map_positions},
+    validate_coordinates, Callback, Cluster, ClusterData, ClusterItem, ClusterPoint, ClusterRow,
+    Columns, DataRange, FeatureBuilder, FilterCache, Float, GeometryMode, InvalidReason, KDBush,
+    LoadReport, Projection, SkippedPoint, SuperclusterBuilder, SuperclusterError,
+    SuperclusterOptions, ValidationPolicy, NO_INDEX,
 };
+#[cfg(feature = "reader")]
+use crate::{read_features, GeoJsonFormat};
 
-/// An offset index used to access the zoom level value associated with a cluster in the data arrays.
-const OFFSET_ZOOM: usize = 2;
-
-/// An offset index used to access the ID associated with a cluster in the data arrays.
-const OFFSET_ID: usize = 3;
-
-/// An offset index used to access the identifier of the parent cluster of a point in the data arrays.
-const OFFSET_PARENT: usize = 4;
-
-/// An offset index used to access the number of points contained within a cluster at the given zoom level in the data arrays.
-const OFFSET_NUM: usize = 5;
-
-/// An offset index used to access the properties associated with a cluster in the data arrays.
-#[cfg(feature = "cluster_metadata")]
-const OFFSET_PROP: usize = 6;
+/// Coordinates at which an input point is clustered, with their index within a multi point if any.
+type Anchor = (Vec<f64>, Option<usize>);
 
 /// Coordinate system for clustering.
 /// The coordinate system is used to determine the range of the incoming data.
@@ -57,12 +73,196 @@ pub enum CoordinateSystem {
         /// Applicable for non-geospatial data (i.e. microscopy, etc.).
         range: DataRange,
     },
+
+    /// Coordinates projected into the clustering space by a user-provided projection,
+    /// e.g. plate carrée, polar stereographic or floor plan coordinates.
+    /// Set with `SuperclusterBuilder::projection`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Callback<dyn Projection>),
+}
+
+impl CoordinateSystem {
+    /// Check whether the x axis of the clustering space wraps around, so the tiles at its edges include the points across the edge.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the clustering space wraps around horizontally, otherwise `false`.
+    fn wraps(&self) -> bool {
+        match self {
+            CoordinateSystem::Custom(projection) => projection.wrap().is_some(),
+            _ => true,
+        }
+    }
+}
+
+/// Clustering algorithm applied at each zoom level.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ClusterAlgorithm {
+    /// Greedy clustering of the neighbors within the cluster radius of each point.
+    #[default]
+    Radius,
+
+    /// Clustering of the points falling into the same cell of a grid, one cell per cluster radius.
+    /// Faster than the radius clustering and independent of the point order within a cell.
+    Grid {
+        /// Position of the clusters within their grid cell.
+        placement: GridPlacement,
+    },
+}
+
+/// Position of a grid cluster within its grid cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum GridPlacement {
+    /// Weighted center of the clustered points.
+    #[default]
+    Centroid,
+
+    /// Center of the grid cell.
+    CellCenter,
+}
+
+/// Position at which a cluster is displayed.
+/// Clustering always uses the weighted mean of the points, the displayed position only affects the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ClusterPosition {
+    /// Weighted mean of the clustered points.
+    #[default]
+    WeightedMean,
+
+    /// Position of the member nearest to the weighted mean.
+    /// The candidates are the displayed positions of the points and clusters merged into the cluster.
+    Medoid,
+
+    /// Position of the point the cluster originated from.
+    Seed,
+
+    /// Position of the clustered point with the highest weight.
+    HighestWeight,
+}
+
+/// Unit of the cluster radius.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum RadiusUnit {
+    /// Pixels relative to the tile extent, so the ground distance merged shrinks with the zoom level and towards the poles.
+    #[default]
+    Pixels,
+
+    /// Meters on the ground, so the clusters have the same ground size at every latitude.
+    /// The neighbors of a point are selected by their great-circle distance.
+    /// Only supported with the `LatLng` coordinate system and the radius algorithm,
+    /// use the zoom schedule to change the radius per zoom level.
+    Meters,
+}
+
+/// Identity of the clusters in the output features.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ClusterIdMode {
+    /// Identify the clusters by their internal ID, which depends on the order and the number of input points.
+    #[default]
+    Index,
+
+    /// Identify the clusters by a hash of their leaf features, so reloading the same points yields the same IDs.
+    /// The leaves are identified by their GeoJSON ID, or by their coordinates if they have no ID.
+    /// The feature ID of a cluster is the hexadecimal hash, and `get_cluster_id` maps it back to the internal ID.
+    Stable,
+}
+
+/// Unique identifier of a cluster within a loaded Supercluster instance.
+/// The ID packs the index of the point the cluster originated from and the zoom level it was created at,
+/// offset by the number of input points so it never collides with the index of an input point.
+/// The zoom level takes 5 bits, or as many bits as needed for `max_zoom`, like the IDs of the JavaScript library.
+/// The origin and the zoom level are decoded with `ClusterId::origin` and `ClusterId::zoom`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ClusterId(pub u64);
+
+impl ClusterId {
+    /// Get the index of the point or cluster the cluster originated from,
+    /// i.e. of its row in the KD-tree of the zoom level above the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `index`: The Supercluster instance that formed the cluster.
+    ///
+    /// # Returns
+    ///
+    /// The index of the row the cluster originated from, otherwise an error if the ID is not a cluster ID of the instance.
+    pub fn origin<T: ClusterPoint>(
+        self,
+        index: &Supercluster<T>,
+    ) -> Result<usize, SuperclusterError> {
+        let id = index.get_cluster_offset(self)?;
+
+        Ok((id >> index.get_zoom_bits()) as usize)
+    }
+
+    /// Get the zoom level of the point or cluster the cluster originated from,
+    /// i.e. the zoom level above the one the cluster was formed at.
+    ///
+    /// # Arguments
+    ///
+    /// - `index`: The Supercluster instance that formed the cluster.
+    ///
+    /// # Returns
+    ///
+    /// The zoom level the cluster originated from, otherwise an error if the ID is not a cluster ID of the instance.
+    pub fn zoom<T: ClusterPoint>(
+        self,
+        index: &Supercluster<T>,
+    ) -> Result<usize, SuperclusterError> {
+        let id = index.get_cluster_offset(self)?;
+        let zoom = (id & ((1 << index.get_zoom_bits()) - 1)) as usize;
+
+        // Clusters are created from zoom level 0, so the encoded zoom is at least 1
+        if zoom == 0 {
+            return Err(SuperclusterError::InvalidClusterId);
+        }
+
+        Ok(zoom)
+    }
+}
+
+impl From<u64> for ClusterId {
+    fn from(id: u64) -> Self {
+        ClusterId(id)
+    }
+}
+
+impl From<ClusterId> for u64 {
+    fn from(id: ClusterId) -> Self {
+        id.0
+    }
+}
+
+impl std::fmt::Display for ClusterId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A change to the input points of a loaded Supercluster instance.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PointChange<T = Feature> {
+    /// Append a feature to the input points.
+    Insert(T),
+
+    /// Replace the feature at the specified index.
+    Update(usize, T),
+
+    /// Remove the feature at the specified index, moving the last feature into its place.
+    Remove(usize),
 }
 
 /// A spatial clustering configuration and data structure.
+/// The input points are GeoJSON features by default, or any type implementing `ClusterPoint`.
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-pub struct Supercluster {
+pub struct Supercluster<T = Feature> {
     /// Configuration settings.
     pub options: SuperclusterOptions,
 
@@ -71,18 +271,45 @@ pub struct Supercluster {
     /// The KD-tree structure is used for spatial indexing.
     pub trees: HashMap<usize, KDBush, BuildHasherDefault<XxHash64>>,
 
-    /// Stride used for data access within the KD-tree.
-    /// The stride is the number of elements in the flat numeric arrays representing point data.
-    pub stride: usize,
+    /// Optional columns stored in the cluster data of the KD-trees.
+    /// Only the columns needed by the options are filled.
+    pub columns: Columns,
 
     /// Input data points.
-    /// A vector of GeoJSON features representing input points to be clustered.
-    pub points: Vec<Feature>,
+    /// A vector of GeoJSON features or other typed points to be clustered.
+    pub points: Vec<T>,
+
+    /// Rows of the point data of the input points in the KD-tree of the input points, by point index.
+    /// A multi point has a row for each of its points, and a skipped point has none.
+    pub point_rows: Vec<Range<usize>>,
+
+    /// Indices of the input points with each GeoJSON ID, in the order they were indexed,
+    /// keyed by the GeoJSON ID tagged with its type.
+    pub point_ids: HashMap<String, Vec<usize>, BuildHasherDefault<XxHash64>>,
+
+    /// Offset of the cluster IDs above the indices of the input points.
+    /// It is the number of input points after `load`, so the cluster IDs match the JavaScript library.
+    pub id_offset: u64,
+
+    /// Partition keys of the input points.
+    /// The partition of a point or a cluster is stored as an index into this vector.
+    pub partitions: Vec<String>,
+
+    /// Indices of the partition keys in `partitions`, keyed by partition key.
+    pub partition_indices: HashMap<String, usize, BuildHasherDefault<XxHash64>>,
 
     /// Clusters metadata.
     /// A vector of JSON objects representing cluster properties.
     #[cfg(feature = "cluster_metadata")]
     pub metadata: Vec<JsonObject>,
+
+    /// Internal cluster IDs by stable cluster ID.
+    /// Only filled with `ClusterIdMode::Stable`.
+    pub stable_ids: HashMap<u64, ClusterId, BuildHasherDefault<XxHash64>>,
+
+    /// Cluster hierarchies of the filtered input points, keyed by filter identifier.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub filters: FilterCache<T>,
 }
 
 impl Supercluster {
@@ -99,7 +326,9 @@ impl Supercluster {
     pub fn feature_builder() -> FeatureBuilder {
         FeatureBuilder::new()
     }
+}
 
+impl<T: ClusterPoint> Supercluster<T> {
     /// Create a new instance of `Supercluster` with the specified configuration settings.
     ///
     /// # Arguments
@@ -113,11 +342,29 @@ impl Supercluster {
         #[cfg(feature = "log")]
         log::debug!("Creating a new supercluster instance");
 
+        // Only store the columns needed by the options, e.g. the cluster properties index when the properties are aggregated
+        let columns = Columns {
+            position: options.position != ClusterPosition::WeightedMean,
+            bbox: options.cluster_bbox,
+            hash: options.id_mode == ClusterIdMode::Stable,
+            #[cfg(feature = "cluster_metadata")]
+            properties: options.reduce.is_some(),
+            #[cfg(not(feature = "cluster_metadata"))]
+            properties: false,
+        };
+
         Supercluster {
             options,
-            stride: 6,
+            columns,
             points: vec![],
+            point_rows: vec![],
+            point_ids: HashMap::default(),
+            id_offset: 0,
+            partitions: vec![],
+            partition_indices: HashMap::default(),
             trees: HashMap::default(),
+            stable_ids: HashMap::default(),
+            filters: FilterCache::default(),
             #[cfg(feature = "cluster_metadata")]
             metadata: vec![],
         }
@@ -130,285 +377,2587 @@ impl Supercluster {
     ///
     /// # Arguments
     ///
-    /// - `points`: A vector of GeoJSON features representing input points to be clustered.
+    /// - `points`: A vector of GeoJSON features or other typed points to be clustered.
     ///
     /// # Returns
     ///
-    /// Supercluster instance with the input points loaded and clustered.
-    pub fn load(&mut self, points: Vec<Feature>) -> Result<&mut Self, SuperclusterError> {
+    /// Supercluster instance with the input points loaded and clustered, and the report of the points skipped
+    /// by the validation policy, otherwise an error if a point is rejected or outside the Cartesian data range.
+    pub fn load(&mut self, points: Vec<T>) -> Result<(&mut Self, LoadReport), SuperclusterError> {
         #[cfg(feature = "log")]
         log::debug!("Loading input {} points into supercluster", points.len());
 
-        let min_zoom = self.options.min_zoom as usize;
-        let max_zoom = self.options.max_zoom as usize;
+        self.load_iter(points)
+    }
 
-        self.points = points;
+    /// Load the input points from an iterator into the Supercluster instance, performing clustering at various zoom levels.
+    /// Each point is indexed as it is consumed and then kept in the `points` of the instance, without an intermediate collection.
+    /// An automatic Cartesian data range is fitted to all the points before they are indexed,
+    /// so they are collected beforehand in that case.
+    ///
+    /// # Arguments
+    ///
+    /// - `points`: An iterator of GeoJSON features or other typed points to be clustered.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance with the input points loaded and clustered, and the report of the skipped points,
+    /// otherwise an error if a point is rejected or outside the Cartesian data range.
+    pub fn load_iter<I>(&mut self, points: I) -> Result<(&mut Self, LoadReport), SuperclusterError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.start_load();
+
+        let mut points = points.into_iter();
+        let fitted: Vec<T> = if self.has_auto_range() {
+            let fitted: Vec<T> = points.by_ref().collect();
+            let bounds = self.get_bounds(&fitted);
+
+            if let CoordinateSystem::Cartesian { range } = &mut self.options.coordinate_system {
+                range.fit(bounds);
+            }
 
-        // Generate a cluster object for each point and index input points into a KD-tree
-        let mut data = vec![];
+            fitted
+        } else {
+            vec![]
+        };
 
         #[cfg(feature = "log")]
         log::debug!("Coordinate system: {:?}", self.options.coordinate_system);
 
-        for (i, feature) in self.points.iter().enumerate() {
-            // Store internal point/cluster data in flat numeric arrays for performance
-            let coordinates = match &feature.geometry {
-                Some(geometry) => match &geometry.value {
-                    Point(coords) => coords,
-                    _ => continue,
-                },
-                None => continue,
-            };
-
-            match &self.options.coordinate_system {
-                CoordinateSystem::Cartesian { range } => {
-                    // X Coordinate
-                    data.push(range.normalize(coordinates[0]));
-
-                    // Y Coordinate
-                    data.push(range.normalize(coordinates[1]));
-                }
-                CoordinateSystem::LatLng => {
-                    // Longitude
-                    data.push(convert_longitude_to_spherical_mercator(coordinates[0]));
-
-                    // Latitude
-                    data.push(convert_latitude_to_spherical_mercator(coordinates[1]));
-                }
-            };
-
-            // The last zoom the point was processed at
-            data.push(f64::INFINITY);
-
-            // Index of the source feature in the original input array
-            data.push(i as f64);
-
-            // Parent cluster id
-            data.push(-1.0);
-
-            // Number of points in a cluster
-            data.push(1.0);
-        }
-
-        let tree = self.create_tree(data);
-        self.trees.insert((max_zoom) + 1, tree);
-
-        // Cluster points on max zoom, then cluster the results on previous zoom, etc.;
-        // Results in a cluster hierarchy across zoom levels
-        for zoom in (min_zoom..=max_zoom).rev() {
-            let next_zoom = zoom + 1;
-
-            // Create a new set of clusters for the zoom and index them with a KD-tree
-            let (previous, current) = self.cluster(
-                self.trees
-                    .get(&next_zoom)
-                    .ok_or(SuperclusterError::TreeNotFound)?,
-                zoom,
-            );
-
-            self.trees
-                .get_mut(&next_zoom)
-                .ok_or(SuperclusterError::TreeNotFound)?
-                .data = previous;
+        // Generate a cluster object for each point and index input points into a KD-tree
+        let capacity = fitted.len() + points.size_hint().0;
+        let mut loaded = Vec::with_capacity(capacity);
+        let mut point_rows = Vec::with_capacity(capacity);
+        let mut data = ClusterData::new(self.columns);
+        let mut report = LoadReport::default();
+
+        for point in fitted.into_iter().chain(points) {
+            if let Err(err) = self.append_point_data(
+                &mut data,
+                &mut point_rows,
+                &mut report,
+                loaded.len(),
+                &point,
+            ) {
+                return Err(self.abort_load(err));
+            }
 
-            let tree = self.create_tree(current);
-            self.trees.insert(zoom, tree);
+            loaded.push(point);
         }
 
-        Ok(self)
+        Ok((self.finish_load(loaded, point_rows, data)?, report))
     }
 
-    /// Retrieve clustered features within the specified bounding box and zoom level.
+    /// Load the features of a GeoJSON input, e.g. a file, into the Supercluster instance.
+    /// The features are parsed one at a time without building a `FeatureCollection`,
+    /// and only the selected properties are kept on the loaded features.
+    /// Each feature is indexed as it is parsed, unless an automatic Cartesian data range has to be fitted to all of them first.
+    /// If the input could not be read or parsed, the instance is left without points.
     ///
     /// # Arguments
     ///
-    /// - `bbox`: The bounding box as an array of four coordinates [min_lng, min_lat, max_lng, max_lat].
-    /// - `zoom`: The zoom level at which to retrieve clusters.
+    /// - `reader`: The reader of the GeoJSON input.
+    /// - `format`: The format of the GeoJSON input, a `FeatureCollection` or newline-delimited GeoJSON.
+    /// - `properties`: The names of the properties kept on the features, otherwise `None` to keep all of them.
     ///
     /// # Returns
     ///
-    /// List of GeoJSON features representing the clusters within the specified bounding box and zoom level.
-    pub fn get_clusters(
-        &self,
-        bbox: [f64; 4],
-        zoom: u8,
-    ) -> Result<Vec<Feature>, SuperclusterError> {
-        #[cfg(feature = "log")]
-        log::debug!(
-            "Retrieving clusters for zoom level {} and bounding box {:?}",
-            zoom,
-            bbox
-        );
-
-        let tree = &self
-            .trees
-            .get(&self.limit_zoom(zoom))
-            .ok_or(SuperclusterError::TreeNotFound)?;
-
-        let ids = match &self.options.coordinate_system {
-            CoordinateSystem::Cartesian { range } => tree.range(
-                range.normalize(bbox[0]),
-                range.normalize(bbox[1]),
-                range.normalize(bbox[2]),
-                range.normalize(bbox[3]),
-            ),
-            CoordinateSystem::LatLng => {
-                let mut min_lng = ((((bbox[0] + 180.0) % 360.0) + 360.0) % 360.0) - 180.0;
-                let min_lat = bbox[1].clamp(-90.0, 90.0);
-                let mut max_lng = if bbox[2] == 180.0 {
-                    180.0
-                } else {
-                    ((((bbox[2] + 180.0) % 360.0) + 360.0) % 360.0) - 180.0
-                };
-                let max_lat = bbox[3].clamp(-90.0, 90.0);
-
-                if bbox[2] - bbox[0] >= 360.0 {
-                    min_lng = -180.0;
-                    max_lng = 180.0;
-                } else if min_lng > max_lng {
-                    let mut eastern_hem = self
-                        .get_clusters([min_lng, min_lat, 180.0, max_lat], zoom)
-                        .unwrap_or_default();
-                    let western_hem = self
-                        .get_clusters([-180.0, min_lat, max_lng, max_lat], zoom)
-                        .unwrap_or_default();
+    /// Supercluster instance with the features loaded and clustered, and the report of the skipped features,
+    /// otherwise an error if the input could not be read or parsed, or a feature is rejected.
+    #[cfg(feature = "reader")]
+    pub fn load_reader<R: Read>(
+        &mut self,
+        reader: R,
+        format: GeoJsonFormat,
+        properties: Option<&[&str]>,
+    ) -> Result<(&mut Self, LoadReport), SuperclusterError>
+    where
+        T: From<Feature>,
+    {
+        if self.has_auto_range() {
+            let mut points = vec![];
+
+            read_features(reader, format, properties, |feature| {
+                points.push(T::from(feature))
+            })
+            .map_err(|err| self.abort_load(err))?;
 
-                    eastern_hem.extend(western_hem);
+            #[cfg(feature = "log")]
+            log::debug!("Read {} features from the input", points.len());
 
-                    return Ok(eastern_hem);
-                }
+            return self.load_iter(points);
+        }
 
-                tree.range(
-                    convert_longitude_to_spherical_mercator(min_lng),
-                    convert_latitude_to_spherical_mercator(max_lat),
-                    convert_longitude_to_spherical_mercator(max_lng),
-                    convert_latitude_to_spherical_mercator(min_lat),
-                )
+        self.start_load();
+
+        let mut points = vec![];
+        let mut point_rows = vec![];
+        let mut data = ClusterData::new(self.columns);
+        let mut report = LoadReport::default();
+        let mut result = Ok(());
+
+        // The rest of the input is still parsed after a rejected point, but no longer indexed
+        let read = read_features(reader, format, properties, |feature| {
+            if result.is_ok() {
+                let point = T::from(feature);
+
+                result = self.append_point_data(
+                    &mut data,
+                    &mut point_rows,
+                    &mut report,
+                    points.len(),
+                    &point,
+                );
+                points.push(point);
             }
-        };
-
-        let mut clusters = vec![];
+        });
 
-        for id in ids {
-            let k = self.stride * id;
-
-            clusters.push(if tree.data[k + OFFSET_NUM] > 1.0 {
-                get_cluster(
-                    &tree.data,
-                    k,
-                    &self.options.coordinate_system,
-                    #[cfg(feature = "cluster_metadata")]
-                    &self.metadata,
-                )
-            } else {
-                self.points[tree.data[k + OFFSET_ID] as usize].to_owned()
-            });
+        if let Err(err) = result.and(read) {
+            return Err(self.abort_load(err));
         }
 
         #[cfg(feature = "log")]
-        log::debug!("Retrieved {} clusters", clusters.len());
+        log::debug!("Read {} features from the input", points.len());
 
-        Ok(clusters)
+        Ok((self.finish_load(points, point_rows, data)?, report))
     }
 
-    /// Retrieve the cluster features for a specified cluster ID.
-    /// The cluster ID is the unique identifier of the cluster.
+    /// Check whether the Cartesian data range is fitted to the input points when they are loaded.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the coordinate system has an automatic Cartesian data range, otherwise `false`.
+    fn has_auto_range(&self) -> bool {
+        matches!(&self.options.coordinate_system, CoordinateSystem::Cartesian { range } if range.auto)
+    }
+
+    /// Clear the partitions and filters of the previous input points before loading new ones.
+    fn start_load(&mut self) {
+        self.partitions.clear();
+        self.partition_indices.clear();
+        self.filters.clear();
+    }
+
+    /// Generate the cluster data of an input point being loaded and append it to the loaded rows.
     ///
     /// # Arguments
     ///
-    /// - `cluster_id`: The unique identifier of the cluster.
+    /// - `data`: The cluster data of the points loaded so far.
+    /// - `point_rows`: The rows of the points loaded so far.
+    /// - `report`: The report of the points skipped so far.
+    /// - `index`: The index of the point in the input points.
+    /// - `point`: The input point.
     ///
     /// # Returns
     ///
-    /// Vector of GeoJSON features representing the cluster with the specified ID.
-    pub fn get_children(&self, cluster_id: usize) -> Result<Vec<Feature>, SuperclusterError> {
-        let origin_id = self.get_origin_id(cluster_id);
-        let origin_zoom = self.get_origin_zoom(cluster_id);
-        let tree = self
-            .trees
-            .get(&origin_zoom)
-            .ok_or(SuperclusterError::TreeNotFound)?;
-        let data = &tree.data;
-
-        if origin_id * self.stride >= data.len() {
-            #[cfg(feature = "log")]
-            log::error!("Cluster not found for ID {}", cluster_id);
-
-            return Err(SuperclusterError::ClusterNotFound);
-        }
-
-        let r = self.options.radius
-            / (self.options.extent * f64::powf(2.0, (origin_zoom as f64) - 1.0));
-
-        let x = data[origin_id * self.stride];
-        let y = data[origin_id * self.stride + 1];
-
-        let ids = tree.within(x, y, r);
-        let mut children = vec![];
+    /// Nothing if the point was indexed or skipped, otherwise an error if it is rejected.
+    fn append_point_data(
+        &mut self,
+        data: &mut ClusterData,
+        point_rows: &mut Vec<Range<usize>>,
+        report: &mut LoadReport,
+        index: usize,
+        point: &T,
+    ) -> Result<(), SuperclusterError> {
+        let rows = self.get_point_data(report, index, point)?;
+        let start = data.len();
+
+        data.append(rows);
+        point_rows.push(start..data.len());
+
+        Ok(())
+    }
 
-        for id in ids {
-            let k = id * self.stride;
+    /// Give up loading the input points after an error.
+    /// An empty index is left rather than the previous one, whose partitions were cleared.
+    ///
+    /// # Arguments
+    ///
+    /// - `err`: The error that stopped the load.
+    ///
+    /// # Returns
+    ///
+    /// The error that stopped the load.
+    fn abort_load(&mut self, err: SuperclusterError) -> SuperclusterError {
+        self.points.clear();
+        self.point_rows.clear();
+        self.point_ids.clear();
+        self.trees.clear();
+
+        err
+    }
 
-            if data[k + OFFSET_PARENT] == (cluster_id as f64) {
-                if data[k + OFFSET_NUM] > 1.0 {
-                    children.push(get_cluster(
-                        data,
-                        k,
-                        &self.options.coordinate_system,
-                        #[cfg(feature = "cluster_metadata")]
-                        &self.metadata,
-                    ));
-                } else {
-                    let point_id = data[k + OFFSET_ID] as usize;
-                    children.push(self.points[point_id].to_owned());
-                }
-            }
-        }
+    /// Keep the loaded input points and cluster them at every zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `points`: The loaded input points.
+    /// - `point_rows`: The rows of each loaded point in the cluster data.
+    /// - `data`: The cluster data of the loaded points.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance with the points clustered, otherwise an error if the clustering failed.
+    fn finish_load(
+        &mut self,
+        points: Vec<T>,
+        point_rows: Vec<Range<usize>>,
+        data: ClusterData,
+    ) -> Result<&mut Self, SuperclusterError> {
+        self.id_offset = points.len() as u64;
+        self.points = points;
+        self.point_rows = point_rows;
+        self.point_ids.clear();
 
-        if children.is_empty() {
-            return Err(SuperclusterError::ClusterNotFound);
+        for index in 0..self.points.len() {
+            self.add_point_id(index);
         }
 
-        Ok(children)
+        self.index(data)
     }
 
-    /// Retrieve individual leaf features within a cluster.
-    /// Leaf features are the individual points within a cluster.
+    /// Insert a feature into the Supercluster instance without reloading the input points.
+    /// The feature is appended to the input points, so its index is the previous number of points.
     ///
     /// # Arguments
     ///
-    /// - `cluster_id`: The unique identifier of the cluster.
-    /// - `limit`: The maximum number of leaf features to retrieve.
-    /// - `offset`: The offset to start retrieving leaf features.
+    /// - `feature`: A GeoJSON feature representing the point to be inserted.
     ///
     /// # Returns
     ///
-    /// A vector of GeoJSON features representing the individual leaf features within the cluster.
-    pub fn get_leaves(&self, cluster_id: usize, limit: usize, offset: usize) -> Vec<Feature> {
-        let mut leaves = vec![];
-        self.append_leaves(&mut leaves, cluster_id, limit, offset, 0);
-
-        leaves
+    /// Supercluster instance with the feature inserted and the clusters updated, and the report of the skipped feature,
+    /// otherwise an error if the feature is rejected.
+    pub fn insert(&mut self, feature: T) -> Result<(&mut Self, LoadReport), SuperclusterError> {
+        self.apply(vec![PointChange::Insert(feature)])
     }
 
-    /// Retrieve a vector of features within a tile at the given zoom level and tile coordinates.
-    /// The tile is a square area of the map that is rendered as an image.
-    /// The zoom level determines the scale of the map.
-    /// The X and Y coordinates determine the position of the tile on the map.
-    /// The tile is represented by a GeoJSON FeatureCollection.
+    /// Replace the feature at the specified index, e.g. to move a point or change its properties.
     ///
     /// # Arguments
     ///
-    /// - `z`: The zoom level of the tile.
-    /// - `x`: The X coordinate of the tile.
-    /// - `y`: The Y coordinate of the tile.
+    /// - `index`: The index of the feature in the input points.
+    /// - `feature`: A GeoJSON feature replacing the existing one.
     ///
     /// # Returns
     ///
-    /// A list of GeoJSON features within the specified tile, otherwise an error if the tile is not found.
-    pub fn get_tile(&self, z: u8, x: f64, y: f64) -> Result<FeatureCollection, SuperclusterError> {
-        let zoom = self.limit_zoom(z);
-        let tree = match self.trees.get(&zoom) {
+    /// Supercluster instance with the feature updated, and the report of the skipped feature,
+    /// otherwise an error if the index is out of bounds.
+    pub fn update(
+        &mut self,
+        index: usize,
+        feature: T,
+    ) -> Result<(&mut Self, LoadReport), SuperclusterError> {
+        self.apply(vec![PointChange::Update(index, feature)])
+    }
+
+    /// Replace the feature with the specified GeoJSON ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `id`: The GeoJSON ID of the feature.
+    /// - `feature`: A GeoJSON feature replacing the existing one.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance with the feature updated, and the report of the skipped feature,
+    /// otherwise an error if no feature has the ID.
+    pub fn update_by_id(
+        &mut self,
+        id: &Id,
+        feature: T,
+    ) -> Result<(&mut Self, LoadReport), SuperclusterError> {
+        let index = self
+            .find_point(id)
+            .ok_or(SuperclusterError::PointNotFound)?;
+
+        self.update(index, feature)
+    }
+
+    /// Remove the feature at the specified index.
+    /// The last feature of the input points takes the place of the removed one, as with `Vec::swap_remove`.
+    ///
+    /// # Arguments
+    ///
+    /// - `index`: The index of the feature in the input points.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance with the feature removed, otherwise an error if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Result<(&mut Self, LoadReport), SuperclusterError> {
+        self.apply(vec![PointChange::Remove(index)])
+    }
+
+    /// Remove the feature with the specified GeoJSON ID.
+    /// The last feature of the input points takes the place of the removed one, as with `Vec::swap_remove`.
+    ///
+    /// # Arguments
+    ///
+    /// - `id`: The GeoJSON ID of the feature.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance with the feature removed, otherwise an error if no feature has the ID.
+    pub fn remove_by_id(&mut self, id: &Id) -> Result<(&mut Self, LoadReport), SuperclusterError> {
+        let index = self
+            .find_point(id)
+            .ok_or(SuperclusterError::PointNotFound)?;
+
+        self.remove(index)
+    }
+
+    /// Apply a batch of changes to the input points and update the clusters once.
+    /// The changes are applied in order, so indices refer to the input points after the previous changes.
+    /// The whole batch is checked before any change is applied, so the instance is left unchanged
+    /// if a change refers to a missing point or a changed point is rejected or outside the Cartesian data range.
+    ///
+    /// Only the changed points are validated and projected, and the zoom levels are clustered again from the stored
+    /// point data, so the clusters, tiles and cluster IDs are identical to loading the changed input points with `load`.
+    /// As after `load`, the cluster IDs depend on the number of input points, use `ClusterIdMode::Stable`
+    /// to identify the clusters across changes. With an automatic Cartesian data range, the input points are
+    /// loaded again when the bounds of the changed points differ from the range, so the range is fitted to them.
+    ///
+    /// # Arguments
+    ///
+    /// - `changes`: The list of changes to apply.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance with the changes applied, and the report of the changed points skipped by the validation policy,
+    /// or of all the skipped points if they were loaded again, otherwise an error if a change refers to a missing point
+    /// or a changed point is rejected or outside the Cartesian data range.
+    pub fn apply(
+        &mut self,
+        changes: Vec<PointChange<T>>,
+    ) -> Result<(&mut Self, LoadReport), SuperclusterError> {
+        #[cfg(feature = "log")]
+        log::debug!("Applying {} changes to supercluster", changes.len());
+
+        self.check_changes(&changes)?;
+
+        self.filters.clear();
+
+        let mut report = LoadReport::default();
+        let mut data = self
+            .trees
+            .remove(&(self.options.max_zoom as usize + 1))
+            .map_or_else(|| ClusterData::new(self.columns), |tree| tree.data);
+
+        for change in changes {
+            match change {
+                PointChange::Insert(feature) => {
+                    self.insert_point_data(&mut data, &mut report, feature)?
+                }
+                PointChange::Update(index, feature) => {
+                    self.update_point_data(&mut data, &mut report, index, feature)?
+                }
+                PointChange::Remove(index) => self.remove_point_data(index)?,
+            }
+        }
+
+        if let CoordinateSystem::Cartesian { range } = &self.options.coordinate_system {
+            let bounds = self.get_bounds(&self.points);
+
+            if range.auto && bounds != [range.min_x, range.min_y, range.max_x, range.max_y] {
+                let points = std::mem::take(&mut self.points);
+
+                return self.load_iter(points);
+            }
+        }
+
+        Ok((self.reindex(data)?, report))
+    }
+
+    /// Check a batch of changes before applying them.
+    ///
+    /// # Arguments
+    ///
+    /// - `changes`: The list of changes to apply.
+    ///
+    /// # Returns
+    ///
+    /// Nothing if every change can be applied, otherwise an error if a change refers to a missing point,
+    /// a changed point is rejected, or the changed points cannot be clustered.
+    fn check_changes(&self, changes: &[PointChange<T>]) -> Result<(), SuperclusterError> {
+        self.options.validate()?;
+
+        // Number of rows of the point data of each input point after the previous changes
+        let mut rows: Vec<usize> = self.point_rows.iter().map(|rows| rows.len()).collect();
+
+        for change in changes {
+            match change {
+                PointChange::Insert(point) => rows.push(self.count_point_rows(rows.len(), point)?),
+                PointChange::Update(index, point) => {
+                    if *index >= rows.len() {
+                        return Err(SuperclusterError::PointNotFound);
+                    }
+
+                    rows[*index] = self.count_point_rows(*index, point)?;
+                }
+                PointChange::Remove(index) => {
+                    if *index >= rows.len() {
+                        return Err(SuperclusterError::PointNotFound);
+                    }
+
+                    rows.swap_remove(*index);
+                }
+            }
+        }
+
+        if !self.fits_cluster_ids(rows.iter().sum(), rows.len() as u64) {
+            return Err(SuperclusterError::TooManyPoints);
+        }
+
+        Ok(())
+    }
+
+    /// Count the rows of the point data of a changed input point.
+    /// A point outside an automatic Cartesian data range is counted, as the range is fitted to it.
+    ///
+    /// # Arguments
+    ///
+    /// - `index`: The index of the point in the input points.
+    /// - `point`: The input point.
+    ///
+    /// # Returns
+    ///
+    /// The number of rows of the point, or zero if the point is skipped,
+    /// otherwise an error if it is rejected or outside the Cartesian data range.
+    fn count_point_rows(&self, index: usize, point: &T) -> Result<usize, SuperclusterError> {
+        match self.get_valid_anchors(point) {
+            Ok(anchors) if self.has_auto_range() => Ok(anchors.len()),
+            Ok(anchors) => Ok(self.get_range_anchors(index, anchors)?.len()),
+            Err(reason) if self.options.validation == ValidationPolicy::Reject => {
+                Err(SuperclusterError::InvalidFeature { index, reason })
+            }
+            Err(_) => Ok(0),
+        }
+    }
+
+    /// Check whether an input point lies outside an automatic Cartesian data range, which must then be fitted again.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The input point.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a valid coordinate of the point is outside the automatic data range, otherwise `false`.
+    fn is_outside_auto_range(&self, point: &T) -> bool {
+        let CoordinateSystem::Cartesian { range } = &self.options.coordinate_system else {
+            return false;
+        };
+
+        range.auto
+            && self.get_valid_anchors(point).is_ok_and(|anchors| {
+                anchors
+                    .iter()
+                    .any(|(coordinates, _)| !range.contains([coordinates[0], coordinates[1]]))
+            })
+    }
+
+    /// Find the index of the feature with the specified GeoJSON ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `id`: The GeoJSON ID of the feature.
+    ///
+    /// # Returns
+    ///
+    /// The index of the feature in the input points, or of the earliest indexed one if several features have the ID,
+    /// otherwise `None` if no feature has the ID.
+    pub fn find_point(&self, id: &Id) -> Option<usize> {
+        self.point_ids
+            .get(&get_id_key(id))
+            .and_then(|indices| indices.first().copied())
+    }
+
+    /// Add the GeoJSON ID of an input point to the indices of the points by ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `index`: The index of the point in the input points.
+    fn add_point_id(&mut self, index: usize) {
+        if let Some(key) = get_point_id_key(&self.points[index]) {
+            self.point_ids.entry(key).or_default().push(index);
+        }
+    }
+
+    /// Remove the GeoJSON ID of an input point from the indices of the points by ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `index`: The index of the point in the input points.
+    fn remove_point_id(&mut self, index: usize) {
+        let Some(key) = get_point_id_key(&self.points[index]) else {
+            return;
+        };
+        let Some(indices) = self.point_ids.get_mut(&key) else {
+            return;
+        };
+
+        indices.retain(|&indexed| indexed != index);
+
+        if indices.is_empty() {
+            self.point_ids.remove(&key);
+        }
+    }
+
+    /// Change the index of an input point in the indices of the points by ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `from`: The previous index of the point in the input points.
+    /// - `to`: The new index of the point in the input points.
+    fn move_point_id(&mut self, from: usize, to: usize) {
+        let indices =
+            get_point_id_key(&self.points[from]).and_then(|key| self.point_ids.get_mut(&key));
+
+        if let Some(indexed) = indices
+            .into_iter()
+            .flatten()
+            .find(|indexed| **indexed == from)
+        {
+            *indexed = to;
+        }
+    }
+
+    /// Index the point data of the input points again, in the order of the input points as `load` does,
+    /// dropping the rows of the removed and replaced points.
+    ///
+    /// # Arguments
+    ///
+    /// - `previous`: The point data the rows of the input points refer to.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance with the point data clustered.
+    fn reindex(&mut self, previous: ClusterData) -> Result<&mut Self, SuperclusterError> {
+        let mut data = ClusterData::new(self.columns);
+
+        for (index, rows) in self.point_rows.iter_mut().enumerate() {
+            let start = data.len();
+
+            for k in rows.clone() {
+                let mut row = previous.row(k);
+
+                // The last point takes the place of a removed one
+                row.id = index as u64;
+                data.push(row);
+            }
+
+            *rows = start..data.len();
+        }
+
+        // Reset the clustering state of the point data
+        data.reset();
+
+        self.id_offset = self.points.len() as u64;
+
+        self.index(data)
+    }
+
+    /// Index the point data into a KD-tree and cluster it at every zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The input point data, ordered by point index.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance with the point data clustered.
+    fn index(&mut self, data: ClusterData) -> Result<&mut Self, SuperclusterError> {
+        let min_zoom = self.options.min_zoom as usize;
+        let max_zoom = self.options.max_zoom as usize;
+
+        self.options.validate()?;
+
+        if !self.fits_cluster_ids(data.len(), self.id_offset) {
+            return Err(SuperclusterError::TooManyPoints);
+        }
+
+        self.trees.clear();
+
+        let tree = self.create_tree(data);
+        self.trees.insert((max_zoom) + 1, tree);
+
+        #[cfg(feature = "cluster_metadata")]
+        let mut metadata = vec![];
+
+        // Cluster points on max zoom, then cluster the results on previous zoom, etc.;
+        // Results in a cluster hierarchy across zoom levels
+        for zoom in (min_zoom..=max_zoom).rev() {
+            let next_zoom = zoom + 1;
+
+            // Create a new set of clusters for the zoom and index them with a KD-tree
+            let (previous, current) = self.cluster(
+                self.trees
+                    .get(&next_zoom)
+                    .ok_or(SuperclusterError::TreeNotFound)?,
+                zoom,
+                #[cfg(feature = "cluster_metadata")]
+                &mut metadata,
+            );
+
+            self.trees
+                .get_mut(&next_zoom)
+                .ok_or(SuperclusterError::TreeNotFound)?
+                .data = previous;
+
+            let tree = self.create_tree(current);
+            self.trees.insert(zoom, tree);
+        }
+
+        #[cfg(feature = "cluster_metadata")]
+        {
+            self.metadata = metadata;
+        }
+
+        self.stable_ids.clear();
+
+        if self.options.id_mode == ClusterIdMode::Stable {
+            for zoom in min_zoom..=max_zoom {
+                let tree = self
+                    .trees
+                    .get(&zoom)
+                    .ok_or(SuperclusterError::TreeNotFound)?;
+
+                for k in 0..tree.data.len() {
+                    if tree.data.is_cluster(k) {
+                        self.stable_ids
+                            .insert(get_stable_id(&tree.data, k), ClusterId(tree.data.id[k]));
+                    }
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Convert an input point into the rows representing its point data.
+    ///
+    /// # Arguments
+    ///
+    /// - `report`: The report the point is added to if it is skipped.
+    /// - `i`: The index of the point in the input points.
+    /// - `point`: The input point.
+    ///
+    /// # Returns
+    ///
+    /// The point data of the point, or no rows if the point has no clustered coordinates or is skipped,
+    /// otherwise an error if the point is rejected or outside the Cartesian data range.
+    fn get_point_data(
+        &mut self,
+        report: &mut LoadReport,
+        i: usize,
+        point: &T,
+    ) -> Result<ClusterData, SuperclusterError> {
+        let mut data = ClusterData::new(self.columns);
+        let anchors = match self.get_valid_anchors(point) {
+            Ok(anchors) => self.get_range_anchors(i, anchors)?,
+            Err(reason) => {
+                #[cfg(feature = "log")]
+                log::warn!("Point {} is invalid: {}", i, reason);
+
+                if self.options.validation == ValidationPolicy::Reject {
+                    return Err(SuperclusterError::InvalidFeature { index: i, reason });
+                }
+
+                report.skipped.push(SkippedPoint { index: i, reason });
+
+                return Ok(data);
+            }
+        };
+
+        if anchors.is_empty() {
+            return Ok(data);
+        }
+
+        // The weight of a multi point is shared between its points
+        let weight = match (&self.options.weight, point.as_feature()) {
+            (Some(weight), Some(feature)) => weight.weight(feature),
+            _ => point.weight(),
+        } / anchors.len() as f64;
+        let partition = self.get_partition(point);
+        let id = point.as_feature().and_then(|feature| feature.id.as_ref());
+
+        for (coordinates, part) in anchors {
+            let (x, y) = self.project(&coordinates);
+
+            // Hash of the point identity, summed over the points in a cluster
+            let hash = match self.options.id_mode {
+                ClusterIdMode::Index => 0,
+                ClusterIdMode::Stable => get_point_hash(id, &coordinates, part),
+            };
+
+            // Store internal point/cluster data in typed columns for performance
+            data.push(ClusterRow {
+                x,
+                y,
+                // The point is not processed at any zoom yet
+                zoom: None,
+                // Index of the source feature in the original input array
+                id: i as u64,
+                parent: None,
+                count: 1,
+                weight,
+                partition,
+                // Displayed position and the weight of the point at that position
+                position: [x, y, weight],
+                bbox: [x, y, x, y],
+                hash,
+                part,
+                properties: None,
+            });
+        }
+
+        Ok(data)
+    }
+
+    /// Compute the bounds of the clustered coordinates of the input points.
+    ///
+    /// # Arguments
+    ///
+    /// - `points`: The input points.
+    ///
+    /// # Returns
+    ///
+    /// The bounds of the points as `[min_x, min_y, max_x, max_y]`, or the unit square if there are no points.
+    fn get_bounds(&self, points: &[T]) -> [f64; 4] {
+        let mut bounds = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+
+        // Invalid points are left out, as they are never clustered
+        for (coords, _) in points
+            .iter()
+            .flat_map(|point| self.get_valid_anchors(point).unwrap_or_default())
+        {
+            bounds = [
+                bounds[0].min(coords[0]),
+                bounds[1].min(coords[1]),
+                bounds[2].max(coords[0]),
+                bounds[3].max(coords[1]),
+            ];
+        }
+
+        if bounds.iter().all(|v| v.is_finite()) {
+            bounds
+        } else {
+            [0.0, 0.0, 1.0, 1.0]
+        }
+    }
+
+    /// Get the coordinates at which an input point is clustered.
+    /// A multi point feature is expanded into one point per position, and the other geometries are represented
+    /// by their anchor point, according to the geometry mode option.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The input point.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates to cluster, with their index within a multi point if any.
+    fn get_anchors(&self, point: &T) -> Vec<Anchor> {
+        let value = match point.as_feature() {
+            Some(feature) => match &feature.geometry {
+                Some(geometry) => &geometry.value,
+                None => return vec![],
+            },
+            None => {
+                return point
+                    .coords()
+                    .map(|coords| vec![(coords.to_vec(), None)])
+                    .unwrap_or_default()
+            }
+        };
+
+        match (value, &self.options.geometry_mode) {
+            (Point(coords), _) => vec![(coords.to_owned(), None)],
+            (_, GeometryMode::PointsOnly) => vec![],
+            (MultiPoint(points), GeometryMode::Anchored { .. }) => points
+                .iter()
+                .enumerate()
+                .map(|(part, coords)| (coords.to_owned(), Some(part)))
+                .collect(),
+            (value, GeometryMode::Anchored { anchor }) => match anchor.anchor(value) {
+                Some(coords) => vec![(coords, None)],
+                None => vec![],
+            },
+        }
+    }
+
+    /// Get the coordinates at which an input point is clustered, validated according to the validation policy.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The input point.
+    ///
+    /// # Returns
+    ///
+    /// The valid or repaired coordinates to cluster, otherwise the reason the point is invalid.
+    fn get_valid_anchors(&self, point: &T) -> Result<Vec<Anchor>, InvalidReason> {
+        if point
+            .as_feature()
+            .is_some_and(|feature| feature.geometry.is_none())
+        {
+            return Err(InvalidReason::MissingGeometry);
+        }
+
+        let lat_lng = self.options.coordinate_system == CoordinateSystem::LatLng;
+
+        self.get_anchors(point)
+            .into_iter()
+            .map(|(coordinates, part)| {
+                validate_coordinates(coordinates, lat_lng, self.options.validation)
+                    .map(|coordinates| (coordinates, part))
+            })
+            .collect()
+    }
+
+    /// Check that the validated coordinates of an input point are within the Cartesian data range,
+    /// clamping them into the range with the clamp policy.
+    ///
+    /// # Arguments
+    ///
+    /// - `i`: The index of the point in the input points.
+    /// - `anchors`: The validated coordinates to cluster.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates within the data range, otherwise an error if a coordinate is outside the range.
+    fn get_range_anchors(
+        &self,
+        i: usize,
+        anchors: Vec<Anchor>,
+    ) -> Result<Vec<Anchor>, SuperclusterError> {
+        let CoordinateSystem::Cartesian { range } = &self.options.coordinate_system else {
+            return Ok(anchors);
+        };
+
+        anchors
+            .into_iter()
+            .map(|(mut coordinates, part)| {
+                let point = [coordinates[0], coordinates[1]];
+
+                if !range.contains(point) {
+                    if self.options.validation != ValidationPolicy::Clamp {
+                        #[cfg(feature = "log")]
+                        log::error!("Point {} is outside the data range {:?}", i, range);
+
+                        return Err(SuperclusterError::OutOfRange(i));
+                    }
+
+                    [coordinates[0], coordinates[1]] = range.clamp_point(point);
+                }
+
+                Ok((coordinates, part))
+            })
+            .collect()
+    }
+
+    /// Project input coordinates into the normalized space used for clustering.
+    ///
+    /// # Arguments
+    ///
+    /// - `coordinates`: The input coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The normalized x and y coordinates.
+    fn project(&self, coordinates: &[f64]) -> (f64, f64) {
+        match &self.options.coordinate_system {
+            CoordinateSystem::Cartesian { range } => {
+                let [x, y] = range.normalize_point([coordinates[0], coordinates[1]]);
+
+                (x, y)
+            }
+            CoordinateSystem::LatLng => (
+                convert_longitude_to_spherical_mercator(coordinates[0]),
+                convert_latitude_to_spherical_mercator(coordinates[1]),
+            ),
+            CoordinateSystem::Custom(projection) => {
+                let [x, y] = projection.forward([coordinates[0], coordinates[1]]);
+
+                (x, y)
+            }
+        }
+    }
+
+    /// Retrieve the query result item of an unclustered point in the cluster data.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: A reference to the cluster data.
+    /// - `k`: The index of the row of the point.
+    ///
+    /// # Returns
+    ///
+    /// The payload of the point, or of the point within a multi point.
+    fn get_leaf(&self, data: &ClusterData, k: usize) -> T::Payload {
+        let point = &self.points[data.id[k] as usize];
+
+        match data.part(k) {
+            Some(part) => point.part_payload(part),
+            None => point.payload(),
+        }
+    }
+
+    /// Retrieve the query result item of a point or a cluster in the cluster data.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: A reference to the cluster data.
+    /// - `k`: The index of the row of the point or cluster.
+    ///
+    /// # Returns
+    ///
+    /// The payload of the point or cluster.
+    fn get_item(&self, data: &ClusterData, k: usize) -> T::Payload {
+        if data.is_cluster(k) {
+            T::cluster_payload(get_cluster(
+                data,
+                k,
+                &self.options,
+                #[cfg(feature = "cluster_metadata")]
+                &self.metadata,
+                &self.partitions,
+            ))
+        } else {
+            self.get_leaf(data, k)
+        }
+    }
+
+    /// Retrieve the typed query result item of a point or a cluster in the cluster data.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: A reference to the cluster data.
+    /// - `k`: The index of the row of the point or cluster.
+    ///
+    /// # Returns
+    ///
+    /// The cluster, or a reference to the input point with its index.
+    fn get_cluster_item(&self, data: &ClusterData, k: usize) -> ClusterItem<&T> {
+        if data.is_cluster(k) {
+            ClusterItem::Cluster(get_cluster(
+                data,
+                k,
+                &self.options,
+                #[cfg(feature = "cluster_metadata")]
+                &self.metadata,
+                &self.partitions,
+            ))
+        } else {
+            let index = data.id[k] as usize;
+
+            ClusterItem::Point {
+                index,
+                part: data.part(k),
+                point: &self.points[index],
+            }
+        }
+    }
+
+    /// Get the index of the partition key of an input point, adding the key to the known partitions.
+    ///
+    /// # Arguments
+    ///
+    /// - `point`: The input point.
+    ///
+    /// # Returns
+    ///
+    /// The index of the partition key, otherwise `None` if the point has no partition key.
+    fn get_partition(&mut self, point: &T) -> Option<usize> {
+        let key = match (&self.options.partition, point.as_feature()) {
+            (Some(partition), Some(feature)) => partition.key(feature),
+            _ => point.partition(),
+        };
+        let key = key?;
+
+        match self.partition_indices.get(&key) {
+            Some(&index) => Some(index),
+            None => {
+                self.partition_indices
+                    .insert(key.clone(), self.partitions.len());
+                self.partitions.push(key);
+                Some(self.partitions.len() - 1)
+            }
+        }
+    }
+
+    /// Append a feature to the input points and its point data.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The point data of the input points, extended with the rows of the feature.
+    /// - `report`: The report the feature is added to if it is skipped.
+    /// - `feature`: The GeoJSON feature to append.
+    ///
+    /// # Returns
+    ///
+    /// An error if the feature is rejected.
+    fn insert_point_data(
+        &mut self,
+        data: &mut ClusterData,
+        report: &mut LoadReport,
+        feature: T,
+    ) -> Result<(), SuperclusterError> {
+        let rows = self.add_point_data(data, report, self.points.len(), &feature)?;
+
+        self.point_rows.push(rows);
+        self.points.push(feature);
+        self.add_point_id(self.points.len() - 1);
+
+        Ok(())
+    }
+
+    /// Replace the feature at the specified index and its point data.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The point data of the input points, extended with the new rows of the feature.
+    /// - `report`: The report the feature is added to if it is skipped.
+    /// - `index`: The index of the feature in the input points.
+    /// - `feature`: The GeoJSON feature replacing the existing one.
+    ///
+    /// # Returns
+    ///
+    /// An error if the index is out of bounds or the feature is rejected.
+    fn update_point_data(
+        &mut self,
+        data: &mut ClusterData,
+        report: &mut LoadReport,
+        index: usize,
+        feature: T,
+    ) -> Result<(), SuperclusterError> {
+        if index >= self.points.len() {
+            return Err(SuperclusterError::PointNotFound);
+        }
+
+        self.point_rows[index] = self.add_point_data(data, report, index, &feature)?;
+        self.remove_point_id(index);
+        self.points[index] = feature;
+        self.add_point_id(index);
+
+        Ok(())
+    }
+
+    /// Remove the feature at the specified index and its point data.
+    /// The last feature takes the place of the removed one.
+    ///
+    /// # Arguments
+    ///
+    /// - `index`: The index of the feature in the input points.
+    ///
+    /// # Returns
+    ///
+    /// An error if the index is out of bounds.
+    fn remove_point_data(&mut self, index: usize) -> Result<(), SuperclusterError> {
+        if index >= self.points.len() {
+            return Err(SuperclusterError::PointNotFound);
+        }
+
+        let last = self.points.len() - 1;
+
+        self.remove_point_id(index);
+
+        if index != last {
+            self.move_point_id(last, index);
+        }
+
+        self.point_rows.swap_remove(index);
+        self.points.swap_remove(index);
+
+        Ok(())
+    }
+
+    /// Project a changed feature and append its point data.
+    /// A feature outside an automatic Cartesian data range gets no rows, as the input points are loaded again.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The point data of the input points, extended with the rows of the feature.
+    /// - `report`: The report the feature is added to if it is skipped.
+    /// - `index`: The index of the feature in the input points.
+    /// - `feature`: The GeoJSON feature.
+    ///
+    /// # Returns
+    ///
+    /// The range of the rows of the feature, otherwise an error if the feature is rejected.
+    fn add_point_data(
+        &mut self,
+        data: &mut ClusterData,
+        report: &mut LoadReport,
+        index: usize,
+        feature: &T,
+    ) -> Result<Range<usize>, SuperclusterError> {
+        let start = data.len();
+
+        if !self.is_outside_auto_range(feature) {
+            data.append(self.get_point_data(report, index, feature)?);
+        }
+
+        Ok(start..data.len())
+    }
+
+    /// Retrieve clustered features within the specified bounding box and zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `bbox`: The bounding box as an array of four coordinates [min_lng, min_lat, max_lng, max_lat].
+    /// - `zoom`: The zoom level at which to retrieve clusters.
+    ///
+    /// # Returns
+    ///
+    /// List of GeoJSON features representing the clusters within the specified bounding box and zoom level.
+    pub fn get_clusters(
+        &self,
+        bbox: [f64; 4],
+        zoom: u8,
+    ) -> Result<Vec<T::Payload>, SuperclusterError> {
+        let (data, rows) = self.get_cluster_rows(bbox, zoom)?;
+
+        Ok(rows.into_iter().map(|k| self.get_item(data, k)).collect())
+    }
+
+    /// Retrieve the typed points and clusters within the specified bounding box and zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `bbox`: The bounding box as an array of four coordinates [min_lng, min_lat, max_lng, max_lat].
+    /// - `zoom`: The zoom level at which to retrieve clusters.
+    ///
+    /// # Returns
+    ///
+    /// List of items representing the points and clusters within the specified bounding box and zoom level.
+    pub fn get_cluster_items(
+        &self,
+        bbox: [f64; 4],
+        zoom: u8,
+    ) -> Result<Vec<ClusterItem<T>>, SuperclusterError>
+    where
+        T: Clone,
+    {
+        Ok(self
+            .get_cluster_items_ref(bbox, zoom)?
+            .into_iter()
+            .map(ClusterItem::cloned)
+            .collect())
+    }
+
+    /// Retrieve the typed points and clusters within the specified bounding box and zoom level,
+    /// borrowing the points instead of cloning them.
+    ///
+    /// # Arguments
+    ///
+    /// - `bbox`: The bounding box as an array of four coordinates [min_lng, min_lat, max_lng, max_lat].
+    /// - `zoom`: The zoom level at which to retrieve clusters.
+    ///
+    /// # Returns
+    ///
+    /// List of items referencing the input points, and the clusters, within the specified bounding box and zoom level.
+    pub fn get_cluster_items_ref(
+        &self,
+        bbox: [f64; 4],
+        zoom: u8,
+    ) -> Result<Vec<ClusterItem<&T>>, SuperclusterError> {
+        let (data, rows) = self.get_cluster_rows(bbox, zoom)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|k| self.get_cluster_item(data, k))
+            .collect())
+    }
+
+    /// Find the points and clusters within the specified bounding box in the cluster data of a zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `bbox`: The bounding box as an array of four coordinates [min_lng, min_lat, max_lng, max_lat].
+    /// - `zoom`: The zoom level at which to retrieve clusters.
+    ///
+    /// # Returns
+    ///
+    /// The cluster data of the zoom level and the indices of the rows of the points and clusters.
+    fn get_cluster_rows(
+        &self,
+        bbox: [f64; 4],
+        zoom: u8,
+    ) -> Result<(&ClusterData, Vec<usize>), SuperclusterError> {
+        #[cfg(feature = "log")]
+        log::debug!(
+            "Retrieving clusters for zoom level {} and bounding box {:?}",
+            zoom,
+            bbox
+        );
+
+        let tree = &self
+            .trees
+            .get(&self.limit_zoom(zoom))
+            .ok_or(SuperclusterError::TreeNotFound)?;
+
+        let ids = match &self.options.coordinate_system {
+            CoordinateSystem::Cartesian { range } => {
+                let [min_x, min_y] = range.normalize_point([bbox[0], bbox[1]]);
+                let [max_x, max_y] = range.normalize_point([bbox[2], bbox[3]]);
+
+                tree.range(min_x, min_y, max_x, max_y)
+            }
+            CoordinateSystem::LatLng => {
+                let mut min_lng = ((((bbox[0] + 180.0) % 360.0) + 360.0) % 360.0) - 180.0;
+                let min_lat = bbox[1].clamp(-90.0, 90.0);
+                let mut max_lng = if bbox[2] == 180.0 {
+                    180.0
+                } else {
+                    ((((bbox[2] + 180.0) % 360.0) + 360.0) % 360.0) - 180.0
+                };
+                let max_lat = bbox[3].clamp(-90.0, 90.0);
+
+                if bbox[2] - bbox[0] >= 360.0 {
+                    min_lng = -180.0;
+                    max_lng = 180.0;
+                } else if min_lng > max_lng {
+                    let (_, mut eastern_hem) =
+                        self.get_cluster_rows([min_lng, min_lat, 180.0, max_lat], zoom)?;
+                    let (_, western_hem) =
+                        self.get_cluster_rows([-180.0, min_lat, max_lng, max_lat], zoom)?;
+
+                    eastern_hem.extend(western_hem);
+
+                    return Ok((&tree.data, eastern_hem));
+                }
+
+                tree.range(
+                    convert_longitude_to_spherical_mercator(min_lng),
+                    convert_latitude_to_spherical_mercator(max_lat),
+                    convert_longitude_to_spherical_mercator(max_lng),
+                    convert_latitude_to_spherical_mercator(min_lat),
+                )
+            }
+            CoordinateSystem::Custom(projection) => {
+                let bboxes = match projection.wrap() {
+                    Some(wrap) => get_wrapped_bboxes(bbox, wrap),
+                    None => vec![bbox],
+                };
+
+                // The projected bounding box may be larger than the query, keep the points within the query only
+                bboxes
+                    .into_iter()
+                    .flat_map(|bbox| {
+                        let [min_x, min_y, max_x, max_y] = projection.forward_bbox(bbox);
+
+                        tree.range(min_x, min_y, max_x, max_y)
+                            .into_iter()
+                            .filter(move |&k| {
+                                let [x, y] = projection.inverse(tree.data.coords(k));
+
+                                x >= bbox[0] && x <= bbox[2] && y >= bbox[1] && y <= bbox[3]
+                            })
+                    })
+                    .collect()
+            }
+        };
+
+        #[cfg(feature = "log")]
+        log::debug!("Retrieved {} clusters", ids.len());
+
+        Ok((&tree.data, ids))
+    }
+
+    /// Retrieve the cluster features for a specified cluster ID.
+    /// The cluster ID is the unique identifier of the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// Vector of GeoJSON features, or other typed items, representing the children of the cluster with the specified ID.
+    pub fn get_children(
+        &self,
+        cluster_id: ClusterId,
+    ) -> Result<Vec<T::Payload>, SuperclusterError> {
+        let (data, children) = self.get_children_data(cluster_id)?;
+
+        Ok(children
+            .into_iter()
+            .map(|k| self.get_item(data, k))
+            .collect())
+    }
+
+    /// Retrieve the typed children of a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// Vector of items representing the points and clusters the cluster with the specified ID is made of.
+    pub fn get_child_items(
+        &self,
+        cluster_id: ClusterId,
+    ) -> Result<Vec<ClusterItem<T>>, SuperclusterError>
+    where
+        T: Clone,
+    {
+        Ok(self
+            .get_child_items_ref(cluster_id)?
+            .into_iter()
+            .map(ClusterItem::cloned)
+            .collect())
+    }
+
+    /// Retrieve the typed children of a cluster, borrowing the points instead of cloning them.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// Vector of items referencing the input points, and the clusters, the cluster with the specified ID is made of.
+    pub fn get_child_items_ref(
+        &self,
+        cluster_id: ClusterId,
+    ) -> Result<Vec<ClusterItem<&T>>, SuperclusterError> {
+        let (data, children) = self.get_children_data(cluster_id)?;
+
+        Ok(children
+            .into_iter()
+            .map(|k| self.get_cluster_item(data, k))
+            .collect())
+    }
+
+    /// Find the children of a cluster in the cluster data of the zoom level below the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// The cluster data of the zoom level and the indices of the rows of the children, otherwise an error if the cluster is not found.
+    fn get_children_data(
+        &self,
+        cluster_id: ClusterId,
+    ) -> Result<(&ClusterData, Vec<usize>), SuperclusterError> {
+        let origin_id = self.get_origin_id(cluster_id)?;
+        let origin_zoom = self.get_origin_zoom(cluster_id)?;
+        let tree = self
+            .trees
+            .get(&origin_zoom)
+            .ok_or(SuperclusterError::TreeNotFound)?;
+        let data = &tree.data;
+
+        if origin_id >= data.len() {
+            #[cfg(feature = "log")]
+            log::error!("Cluster not found for ID {}", cluster_id);
+
+            return Err(SuperclusterError::ClusterNotFound);
+        }
+
+        // The parents of the children are stored in the zoom level below
+        let parents = &self
+            .trees
+            .get(&(origin_zoom - 1))
+            .ok_or(SuperclusterError::TreeNotFound)?
+            .data;
+
+        let [x, y] = data.coords(origin_id);
+        let mut r = get_search_radius(&self.options, origin_zoom - 1, y);
+
+        // Grid clusters may be up to the diagonal of their cell away from their children
+        if let ClusterAlgorithm::Grid { .. } = self.options.algorithm {
+            r *= SQRT_2;
+        }
+
+        let ids = if is_periodic(&self.options) {
+            tree.within_wrapped(x, y, r)
+        } else {
+            tree.within(x, y, r)
+        };
+
+        let children: Vec<usize> = ids
+            .into_iter()
+            .filter(|k| {
+                data.parent(*k)
+                    .is_some_and(|parent| parents.id[parent] == cluster_id.0)
+            })
+            .collect();
+
+        if children.is_empty() {
+            return Err(SuperclusterError::ClusterNotFound);
+        }
+
+        Ok((data, children))
+    }
+
+    /// Retrieve individual leaf features within a cluster.
+    /// Leaf features are the individual points within a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    /// - `limit`: The maximum number of leaf features to retrieve.
+    /// - `offset`: The offset to start retrieving leaf features.
+    ///
+    /// # Returns
+    ///
+    /// A vector of GeoJSON features representing the individual leaf features within the cluster.
+    pub fn get_leaves(
+        &self,
+        cluster_id: ClusterId,
+        limit: usize,
+        offset: usize,
+    ) -> Vec<T::Payload> {
+        let mut leaves = vec![];
+        self.append_leaves(&mut leaves, cluster_id, limit, offset, 0);
+
+        leaves
+    }
+
+    /// Retrieve the typed individual points within a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    /// - `limit`: The maximum number of points to retrieve.
+    /// - `offset`: The offset to start retrieving points.
+    ///
+    /// # Returns
+    ///
+    /// A vector of point items representing the individual points within the cluster.
+    pub fn get_leaf_items(
+        &self,
+        cluster_id: ClusterId,
+        limit: usize,
+        offset: usize,
+    ) -> Vec<ClusterItem<T>>
+    where
+        T: Clone,
+    {
+        self.get_leaf_items_ref(cluster_id, limit, offset)
+            .into_iter()
+            .map(ClusterItem::cloned)
+            .collect()
+    }
+
+    /// Retrieve the typed individual points within a cluster, borrowing the points instead of cloning them.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    /// - `limit`: The maximum number of points to retrieve.
+    /// - `offset`: The offset to start retrieving points.
+    ///
+    /// # Returns
+    ///
+    /// A vector of point items referencing the individual points within the cluster.
+    pub fn get_leaf_items_ref(
+        &self,
+        cluster_id: ClusterId,
+        limit: usize,
+        offset: usize,
+    ) -> Vec<ClusterItem<&T>> {
+        let mut rows = vec![];
+        self.append_leaf_rows(&mut rows, cluster_id, limit, offset, 0);
+
+        rows.into_iter()
+            .map(|(data, k)| self.get_cluster_item(data, k))
+            .collect()
+    }
+
+    /// Retrieve the internal ID of a cluster from its stable ID.
+    /// Stable IDs are only available with `ClusterIdMode::Stable`.
+    ///
+    /// # Arguments
+    ///
+    /// - `stable_id`: The stable ID of the cluster, as found in the cluster feature ID.
+    ///
+    /// # Returns
+    ///
+    /// The internal ID of the cluster, otherwise an error if no cluster has the stable ID.
+    pub fn get_cluster_id(&self, stable_id: &str) -> Result<ClusterId, SuperclusterError> {
+        u64::from_str_radix(stable_id, 16)
+            .ok()
+            .and_then(|stable_id| self.stable_ids.get(&stable_id))
+            .copied()
+            .ok_or(SuperclusterError::ClusterNotFound)
+    }
+
+    /// Retrieve the cluster hierarchy of the input points matching a predicate.
+    /// The hierarchy is built on the first call for a filter identifier and cached for the following calls,
+    /// so the same identifier must always be used with the same predicate.
+    /// The cache is cleared whenever the input points change.
+    ///
+    /// The filtered hierarchy has its own cluster IDs, but no input points of its own:
+    /// its leaves refer to the input points of this instance by index, so its queries go through the `_filtered` methods.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter.
+    /// - `predicate`: The predicate over the original points selecting the points to cluster.
+    ///
+    /// # Returns
+    ///
+    /// Supercluster instance clustering only the matching points.
+    fn filtered<F>(
+        &self,
+        filter_id: &str,
+        predicate: F,
+    ) -> Result<Arc<Supercluster<T>>, SuperclusterError>
+    where
+        F: Fn(&T) -> bool,
+    {
+        if let Some(index) = self.filters.get(filter_id) {
+            return Ok(index);
+        }
+
+        #[cfg(feature = "log")]
+        log::debug!("Clustering points for filter {}", filter_id);
+
+        let (source, points): (Vec<usize>, Vec<&T>) = self
+            .points
+            .iter()
+            .enumerate()
+            .filter(|(_, point)| predicate(point))
+            .unzip();
+
+        // The matching points are projected with the data range fitted to all the points
+        let mut options = self.options.clone();
+
+        if let CoordinateSystem::Cartesian { range } = &mut options.coordinate_system {
+            range.auto = false;
+        }
+
+        let mut index = Supercluster::new(options);
+        index.load(points)?;
+
+        let Supercluster {
+            options,
+            mut trees,
+            columns,
+            id_offset,
+            partitions,
+            partition_indices,
+            #[cfg(feature = "cluster_metadata")]
+            metadata,
+            stable_ids,
+            ..
+        } = index;
+
+        // The leaves refer to the matching points, replace them with the indices of the input points
+        for tree in trees.values_mut() {
+            for k in 0..tree.data.len() {
+                if !tree.data.is_cluster(k) {
+                    tree.data.id[k] = source[tree.data.id[k] as usize] as u64;
+                }
+            }
+        }
+
+        let index = Arc::new(Supercluster {
+            options,
+            trees,
+            columns,
+            points: vec![],
+            point_rows: vec![],
+            point_ids: HashMap::default(),
+            id_offset,
+            partitions,
+            partition_indices,
+            #[cfg(feature = "cluster_metadata")]
+            metadata,
+            stable_ids,
+            filters: FilterCache::default(),
+        });
+        self.filters.insert(filter_id, index.clone());
+
+        Ok(index)
+    }
+
+    /// Retrieve the query result item of a point or a cluster in the cluster data of a filtered hierarchy.
+    ///
+    /// # Arguments
+    ///
+    /// - `index`: The filtered cluster hierarchy.
+    /// - `data`: A reference to the cluster data of the filtered hierarchy.
+    /// - `k`: The index of the row of the point or cluster.
+    ///
+    /// # Returns
+    ///
+    /// The payload of the cluster, or of the input point of this instance.
+    fn get_filtered_item(
+        &self,
+        index: &Supercluster<T>,
+        data: &ClusterData,
+        k: usize,
+    ) -> T::Payload {
+        if data.is_cluster(k) {
+            index.get_item(data, k)
+        } else {
+            self.get_leaf(data, k)
+        }
+    }
+
+    /// Retrieve clustered features of the points matching a predicate within the specified bounding box and zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter, used to cache the filtered cluster hierarchy.
+    /// - `predicate`: The predicate over the original points selecting the points to cluster.
+    /// - `bbox`: The bounding box as an array of four coordinates [min_lng, min_lat, max_lng, max_lat].
+    /// - `zoom`: The zoom level at which to retrieve clusters.
+    ///
+    /// # Returns
+    ///
+    /// List of GeoJSON features, or other typed items, representing the clusters of the matching points.
+    pub fn get_clusters_filtered<F>(
+        &self,
+        filter_id: &str,
+        predicate: F,
+        bbox: [f64; 4],
+        zoom: u8,
+    ) -> Result<Vec<T::Payload>, SuperclusterError>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let index = self.filtered(filter_id, predicate)?;
+        let (data, rows) = index.get_cluster_rows(bbox, zoom)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|k| self.get_filtered_item(&index, data, k))
+            .collect())
+    }
+
+    /// Retrieve the children of a cluster returned from the filtered queries.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter, used to cache the filtered cluster hierarchy.
+    /// - `predicate`: The predicate over the original points selecting the points to cluster.
+    /// - `cluster_id`: The unique identifier of the cluster in the filtered hierarchy.
+    ///
+    /// # Returns
+    ///
+    /// Vector of GeoJSON features, or other typed items, representing the children of the cluster with the specified ID.
+    pub fn get_children_filtered<F>(
+        &self,
+        filter_id: &str,
+        predicate: F,
+        cluster_id: ClusterId,
+    ) -> Result<Vec<T::Payload>, SuperclusterError>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let index = self.filtered(filter_id, predicate)?;
+        let (data, children) = index.get_children_data(cluster_id)?;
+
+        Ok(children
+            .into_iter()
+            .map(|k| self.get_filtered_item(&index, data, k))
+            .collect())
+    }
+
+    /// Retrieve individual leaf features within a cluster returned from the filtered queries.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter, used to cache the filtered cluster hierarchy.
+    /// - `predicate`: The predicate over the original points selecting the points to cluster.
+    /// - `cluster_id`: The unique identifier of the cluster in the filtered hierarchy.
+    /// - `limit`: The maximum number of leaf features to retrieve.
+    /// - `offset`: The offset to start retrieving leaf features.
+    ///
+    /// # Returns
+    ///
+    /// A vector of GeoJSON features representing the matching points within the cluster.
+    pub fn get_leaves_filtered<F>(
+        &self,
+        filter_id: &str,
+        predicate: F,
+        cluster_id: ClusterId,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<T::Payload>, SuperclusterError>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let index = self.filtered(filter_id, predicate)?;
+        let mut rows = vec![];
+        index.append_leaf_rows(&mut rows, cluster_id, limit, offset, 0);
+
+        Ok(rows
+            .into_iter()
+            .map(|(data, k)| self.get_leaf(data, k))
+            .collect())
+    }
+
+    /// Determine the zoom level at which a cluster returned from the filtered queries expands.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter, used to cache the filtered cluster hierarchy.
+    /// - `predicate`: The predicate over the original points selecting the points to cluster.
+    /// - `cluster_id`: The unique identifier of the cluster in the filtered hierarchy.
+    ///
+    /// # Returns
+    ///
+    /// The zoom level at which the cluster expands, otherwise an error if the cluster ID is invalid.
+    pub fn get_cluster_expansion_zoom_filtered<F>(
+        &self,
+        filter_id: &str,
+        predicate: F,
+        cluster_id: ClusterId,
+    ) -> Result<usize, SuperclusterError>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.filtered(filter_id, predicate)?
+            .get_cluster_expansion_zoom(cluster_id)
+    }
+
+    /// Remove the cached cluster hierarchy of a filter, e.g. after changing its predicate.
+    ///
+    /// # Arguments
+    ///
+    /// - `filter_id`: The identifier of the filter.
+    pub fn invalidate_filter(&self, filter_id: &str) {
+        self.filters.remove(filter_id);
+    }
+
+    /// Determine the zoom level at which a specific cluster expands.
+    /// The cluster expands when it contains more than one child cluster.
+    /// The cluster expands until it reaches the maximum zoom level or contains more than one child cluster.
+    /// The zoom level at which the cluster expands is the zoom level at which the cluster contains more than one child cluster.
+    /// The cluster does not expand if it contains only one child cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// The zoom level at which the cluster expands, otherwise an error if the cluster ID is invalid.
+    pub fn get_cluster_expansion_zoom(
+        &self,
+        mut cluster_id: ClusterId,
+    ) -> Result<usize, SuperclusterError> {
+        let mut expansion_zoom = self.get_origin_zoom(cluster_id)? - 1;
+
+        while expansion_zoom <= (self.options.max_zoom as usize) {
+            let (data, children) = match self.get_children_data(cluster_id) {
+                Ok(children) => children,
+                Err(_) => break,
+            };
+
+            expansion_zoom += 1;
+
+            // Stop when the cluster splits or its only child is a point
+            if children.len() != 1 || !data.is_cluster(children[0]) {
+                break;
+            }
+
+            cluster_id = ClusterId(data.id[children[0]]);
+        }
+
+        Ok(expansion_zoom)
+    }
+
+    /// Appends leaves (features) to the result vector based on the specified criteria.
+    /// The method is used to collect leaves within a cluster based on the specified limit and offset.
+    /// The method is called recursively to collect leaves within child clusters.
+    /// The method is used internally by the `get_leaves` method.
+    ///
+    /// # Arguments
+    ///
+    /// - `result`: A mutable reference to a vector where leaves will be appended.
+    /// - `cluster_id`: The identifier of the cluster whose leaves are being collected.
+    /// - `limit`: The maximum number of leaves to collect.
+    /// - `offset`: The number of leaves to skip before starting to collect.
+    /// - `skipped`: The current count of skipped leaves, used for tracking the progress.
+    ///
+    /// # Returns
+    ///
+    /// The updated count of skipped leaves after processing the current cluster.
+    pub fn append_leaves(
+        &self,
+        result: &mut Vec<T::Payload>,
+        cluster_id: ClusterId,
+        limit: usize,
+        offset: usize,
+        skipped: usize,
+    ) -> usize {
+        let mut rows = vec![];
+        let skipped = self.append_leaf_rows(
+            &mut rows,
+            cluster_id,
+            limit.saturating_sub(result.len()),
+            offset,
+            skipped,
+        );
+
+        result.extend(rows.into_iter().map(|(data, k)| self.get_leaf(data, k)));
+
+        skipped
+    }
+
+    /// Appends the rows of the leaves of a cluster to the result vector, like `append_leaves`.
+    ///
+    /// # Arguments
+    ///
+    /// - `result`: A mutable reference to a vector where the cluster data and the indices of the rows of the leaves will be appended.
+    /// - `cluster_id`: The identifier of the cluster whose leaves are being collected.
+    /// - `limit`: The maximum number of leaves to collect.
+    /// - `offset`: The number of leaves to skip before starting to collect.
+    /// - `skipped`: The current count of skipped leaves, used for tracking the progress.
+    ///
+    /// # Returns
+    ///
+    /// The updated count of skipped leaves after processing the current cluster.
+    fn append_leaf_rows<'a>(
+        &'a self,
+        result: &mut Vec<(&'a ClusterData, usize)>,
+        cluster_id: ClusterId,
+        limit: usize,
+        offset: usize,
+        mut skipped: usize,
+    ) -> usize {
+        let (data, children) = match self.get_children_data(cluster_id) {
+            Ok(children) => children,
+            Err(_) => return skipped,
+        };
+
+        for k in children {
+            if data.is_cluster(k) {
+                let point_count = data.count[k] as usize;
+
+                if skipped + point_count <= offset {
+                    // Skip the whole cluster
+                    skipped += point_count;
+                } else {
+                    // Enter the cluster
+                    skipped = self.append_leaf_rows(
+                        result,
+                        ClusterId(data.id[k]),
+                        limit,
+                        offset,
+                        skipped,
+                    );
+                    // Exit the cluster
+                }
+            } else if skipped < offset {
+                // Skip a single point
+                skipped += 1;
+            } else {
+                // Add a single point
+                result.push((data, k));
+            }
+
+            if result.len() == limit {
+                break;
+            }
+        }
+
+        skipped
+    }
+
+    /// Create a KD-tree using the specified data, which is used for spatial indexing.
+    /// The KD-tree is used to index the input points for clustering.
+    /// The KD-tree is created for the specified data and configured with the specified node size.
+    /// The KD-tree is used to cluster points based on the specified node size.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The cluster data of the points and clusters indexed by the KD-tree.
+    ///
+    /// # Returns
+    ///
+    /// `KDBush` instance with the specified data.
+    pub fn create_tree(&mut self, data: ClusterData) -> KDBush {
+        let mut tree = KDBush::new(data.len(), self.options.node_size);
+
+        for k in 0..data.len() {
+            let [x, y] = data.coords(k);
+
+            tree.add_point(x, y);
+        }
+
+        tree.build_index();
+        tree.data = data;
+
+        tree
+    }
+
+    /// Calculate the effective zoom level that takes into account the configured minimum and maximum zoom levels.
+    /// The effective zoom level is the zoom level that is within the configured minimum and maximum zoom levels.
+    ///
+    /// # Arguments
+    ///
+    /// - `zoom`: The initial zoom level.
+    ///
+    /// # Returns
+    ///
+    /// The effective zoom level considering the configured minimum and maximum zoom levels.
+    pub fn limit_zoom(&self, zoom: u8) -> usize {
+        #[cfg(feature = "log")]
+        log::debug!("Limiting zoom level to {}", zoom);
+
+        zoom.max(self.options.min_zoom)
+            .min(self.options.max_zoom + 1) as usize
+    }
+
+    /// Cluster points on a given zoom level with the configured algorithm and returns updated cluster data.
+    ///
+    /// # Arguments
+    ///
+    /// - `tree`: A reference to the KD-tree structure for spatial indexing.
+    /// - `zoom`: The zoom level at which clustering is performed.
+    /// - `metadata`: The cluster metadata, extended with the aggregated properties of the new clusters.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two cluster data: the first one contains updated cluster data for the current zoom level,
+    /// and the second one contains cluster data for the next zoom level.
+    pub fn cluster(
+        &self,
+        tree: &KDBush,
+        zoom: usize,
+        #[cfg(feature = "cluster_metadata")] metadata: &mut Vec<JsonObject>,
+    ) -> (ClusterData, ClusterData) {
+        #[cfg(feature = "log")]
+        log::debug!("Clustering points at zoom level {}", zoom);
+
+        let mut data = tree.data.to_owned();
+        let mut next_data = ClusterData::new(self.columns);
+
+        match &self.options.algorithm {
+            ClusterAlgorithm::Radius => {
+                #[cfg(feature = "rayon")]
+                self.cluster_radius_chunks(
+                    tree,
+                    &mut data,
+                    &mut next_data,
+                    zoom,
+                    #[cfg(feature = "cluster_metadata")]
+                    metadata,
+                );
+
+                #[cfg(not(feature = "rayon"))]
+                self.cluster_radius(
+                    tree,
+                    &mut data,
+                    &mut next_data,
+                    zoom,
+                    #[cfg(feature = "cluster_metadata")]
+                    metadata,
+                );
+            }
+            ClusterAlgorithm::Grid { placement } => {
+                let rows = 0..data.len();
+
+                self.merge_cells(
+                    &mut data,
+                    &mut next_data,
+                    rows,
+                    zoom,
+                    *placement,
+                    #[cfg(feature = "cluster_metadata")]
+                    metadata,
+                );
+            }
+        }
+
+        (data, next_data)
+    }
+
+    /// Cluster the points within the cluster radius of each other, visiting the points one at a time.
+    ///
+    /// # Arguments
+    ///
+    /// - `tree`: A reference to the KD-tree of the zoom level.
+    /// - `data`: The cluster data of the current zoom level.
+    /// - `next_data`: The cluster data of the next zoom level, extended with the new clusters and the unclustered points.
+    /// - `zoom`: The zoom level at which clustering is performed.
+    /// - `metadata`: The cluster metadata, extended with the aggregated properties of the new clusters.
+    #[cfg(any(test, not(feature = "rayon")))]
+    fn cluster_radius(
+        &self,
+        tree: &KDBush,
+        data: &mut ClusterData,
+        next_data: &mut ClusterData,
+        zoom: usize,
+        #[cfg(feature = "cluster_metadata")] metadata: &mut Vec<JsonObject>,
+    ) {
+        // Loop through each point
+        for i in 0..data.len() {
+            // If we've already visited the point at this zoom level, skip it
+            if data.zoom[i] <= zoom as u8 {
+                continue;
+            }
+
+            data.zoom[i] = zoom as u8;
+
+            // Find all nearby points
+            let neighbor_ids = get_neighbors(&self.options, tree, data, i, zoom);
+
+            self.merge(
+                data,
+                next_data,
+                i,
+                neighbor_ids,
+                zoom,
+                #[cfg(feature = "cluster_metadata")]
+                metadata,
+            );
+        }
+    }
+
+    /// Cluster the points within the cluster radius of each other like `cluster_radius`,
+    /// searching the neighbors of a chunk of points in parallel before merging them sequentially.
+    ///
+    /// # Arguments
+    ///
+    /// - `tree`: A reference to the KD-tree of the zoom level.
+    /// - `data`: The cluster data of the current zoom level.
+    /// - `next_data`: The cluster data of the next zoom level, extended with the new clusters and the unclustered points.
+    /// - `zoom`: The zoom level at which clustering is performed.
+    /// - `metadata`: The cluster metadata, extended with the aggregated properties of the new clusters.
+    #[cfg(feature = "rayon")]
+    fn cluster_radius_chunks(
+        &self,
+        tree: &KDBush,
+        data: &mut ClusterData,
+        next_data: &mut ClusterData,
+        zoom: usize,
+        #[cfg(feature = "cluster_metadata")] metadata: &mut Vec<JsonObject>,
+    ) {
+        let options = &self.options;
+
+        for start in (0..data.len()).step_by(NEIGHBORS_CHUNK_SIZE) {
+            let end = (start + NEIGHBORS_CHUNK_SIZE).min(data.len());
+
+            // The points visited by the previous chunks are left out
+            let neighbors: Vec<Option<Vec<usize>>> = (start..end)
+                .into_par_iter()
+                .map(|k| {
+                    (data.zoom[k] > zoom as u8).then(|| get_neighbors(options, tree, data, k, zoom))
+                })
+                .collect();
+
+            for (i, neighbor_ids) in (start..end).zip(neighbors) {
+                // If we've already visited the point at this zoom level, skip it
+                let Some(neighbor_ids) = neighbor_ids.filter(|_| data.zoom[i] > zoom as u8) else {
+                    continue;
+                };
+
+                data.zoom[i] = zoom as u8;
+
+                self.merge(
+                    data,
+                    next_data,
+                    i,
+                    neighbor_ids,
+                    zoom,
+                    #[cfg(feature = "cluster_metadata")]
+                    metadata,
+                );
+            }
+        }
+    }
+
+    /// Bin rows into grid cells and merge the rows of each cell into a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The cluster data of the current zoom level.
+    /// - `next_data`: The cluster data of the next zoom level, extended with the new clusters or the unclustered points.
+    /// - `rows`: The indices of the rows to bin, in order, including every row of their cells.
+    /// - `zoom`: The zoom level at which clustering is performed.
+    /// - `placement`: The position of the clusters within their cells.
+    /// - `metadata`: The cluster metadata, extended with the aggregated properties of the new clusters.
+    fn merge_cells(
+        &self,
+        data: &mut ClusterData,
+        next_data: &mut ClusterData,
+        rows: impl IntoIterator<Item = usize>,
+        zoom: usize,
+        placement: GridPlacement,
+        #[cfg(feature = "cluster_metadata")] metadata: &mut Vec<JsonObject>,
+    ) {
+        // The grid only supports a radius in pixels, which does not depend on the latitude
+        let r = get_search_radius(&self.options, zoom, 0.5);
+        let periodic = is_periodic(&self.options);
+        let mut cells: HashMap<(i64, i64, i64), usize, BuildHasherDefault<XxHash64>> =
+            HashMap::default();
+        let mut groups: Vec<((i64, i64, i64), Vec<usize>)> = vec![];
+
+        // Bin the points of each partition into grid cells, keeping the cells in the order of their first point
+        for i in rows {
+            let cell = get_cell(data, i, r, periodic);
+            let group = *cells.entry(cell).or_insert_with(|| {
+                groups.push((cell, vec![]));
+                groups.len() - 1
+            });
+
+            groups[group].1.push(i);
+        }
+
+        // The first point of a cell is the origin of its cluster
+        for ((cx, cy, _), ids) in groups {
+            let i = ids[0];
+            let start = next_data.len();
+
+            data.zoom[i] = zoom as u8;
+
+            let clustered = self.merge(
+                data,
+                next_data,
+                i,
+                ids,
+                zoom,
+                #[cfg(feature = "cluster_metadata")]
+                metadata,
+            );
+
+            if clustered && placement == GridPlacement::CellCenter {
+                next_data.x[start] = ((cx as f64 + 0.5) * r) as Float;
+                next_data.y[start] = ((cy as f64 + 0.5) * r) as Float;
+            }
+        }
+    }
+
+    /// Merge the unvisited neighbors of a point into a new cluster if they form one,
+    /// otherwise carry the point and its neighbors over to the next zoom level.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The cluster data of the current zoom level.
+    /// - `next_data`: The cluster data of the next zoom level, extended with the new cluster or the unclustered points.
+    /// - `i`: The index of the row of the point the cluster originates from.
+    /// - `neighbor_ids`: The candidate neighbors of the point.
+    /// - `zoom`: The zoom level at which clustering is performed.
+    /// - `metadata`: The cluster metadata, extended with the aggregated properties of the new cluster.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a new cluster was formed, otherwise `false`.
+    fn merge(
+        &self,
+        data: &mut ClusterData,
+        next_data: &mut ClusterData,
+        i: usize,
+        neighbor_ids: Vec<usize>,
+        zoom: usize,
+        #[cfg(feature = "cluster_metadata")] metadata: &mut Vec<JsonObject>,
+    ) -> bool {
+        let num_points_origin = data.count[i];
+        let mut num_points = num_points_origin;
+        let mut weight = data.weight[i];
+        let partition = data.partition[i];
+
+        // Count the number of points in a potential cluster
+        for &k in &neighbor_ids {
+            // Filter out neighbors that are already processed or belong to another partition
+            if data.zoom[k] > zoom as u8 && data.partition[k] == partition {
+                num_points += data.count[k];
+                weight += data.weight[k];
+            }
+        }
+
+        // If there were neighbors to merge, and there is enough weight to form a cluster
+        if num_points > num_points_origin && weight >= (self.options.min_points_at(zoom) as f64) {
+            // Fall back to the number of points if the cluster has no weight at all
+            let weighted = weight > 0.0;
+            let total_weight = if weighted { weight } else { num_points as f64 };
+            let point_weight = |data: &ClusterData, k: usize| {
+                if weighted {
+                    data.weight[k]
+                } else {
+                    data.count[k] as f64
+                }
+            };
+
+            let periodic = is_periodic(&self.options);
+            let [x, y] = data.coords(i);
+            let mut wx = x * point_weight(data, i);
+            let mut wy = y * point_weight(data, i);
+
+            #[cfg(feature = "cluster_metadata")]
+            let mut properties: Option<JsonObject> = None;
+
+            // Encode both zoom and point index on which the cluster originated -- offset by total length of features
+            let id = self.get_new_cluster_id(i, zoom).0;
+
+            // The new cluster is the parent of the merged points and clusters
+            let parent = next_data.len() as u32;
+
+            // Points and clusters merged into the cluster, starting with the origin
+            let mut members = vec![i];
+
+            for k in neighbor_ids {
+                if data.zoom[k] <= zoom as u8 || data.partition[k] != partition {
+                    continue;
+                }
+
+                // Save the zoom (so it doesn't get processed twice)
+                data.zoom[k] = zoom as u8;
+
+                let weight2 = point_weight(data, k);
+
+                // Accumulate coordinates for calculating weighted center
+                let [x2, y2] = data.coords(k);
+
+                // Members across the antimeridian are averaged on the near side of the origin
+                wx += (x2 + get_x_shift(x2, x, periodic)) * weight2;
+                wy += y2 * weight2;
+
+                data.parent[k] = parent;
+                members.push(k);
+
+                #[cfg(feature = "cluster_metadata")]
+                if let Some(reduce) = &self.options.reduce {
+                    let properties =
+                        properties.get_or_insert_with(|| self.map_properties(data, i, metadata));
+
+                    reduce(properties, &self.map_properties(data, k, metadata));
+                }
+            }
+
+            data.parent[i] = parent;
+
+            // Move the weighted center back into the clustering space if it crossed an edge
+            let cx = wx / total_weight;
+            let shift = if periodic { -cx.floor() } else { 0.0 };
+            let (cx, cy) = (cx + shift, wy / total_weight);
+            let representative = self.get_representative(data, &members, cx, cy);
+
+            // Extend the bounding box over the merged points and clusters, moving each member next to the origin,
+            // so the bounding box of a cluster across the antimeridian may extend past the edges of the clustering space
+            let bbox = members.iter().fold(
+                [
+                    f64::INFINITY,
+                    f64::INFINITY,
+                    f64::NEG_INFINITY,
+                    f64::NEG_INFINITY,
+                ],
+                |bbox, k| {
+                    let member = data.bbox(*k);
+                    let [x2, _] = data.coords(*k);
+                    let dx = get_x_shift(x2, x, periodic) + shift;
+
+                    [
+                        bbox[0].min(member[0] + dx),
+                        bbox[1].min(member[1]),
+                        bbox[2].max(member[2] + dx),
+                        bbox[3].max(member[3]),
+                    ]
+                },
+            );
+
+            // Sum the member hashes, so the hash of a cluster does not depend on the merge order
+            let hash = members
+                .iter()
+                .fold(0u64, |hash, k| hash.wrapping_add(data.hash(*k)));
+
+            #[cfg(feature = "cluster_metadata")]
+            let properties = properties.map(|properties| {
+                metadata.push(properties);
+                metadata.len() - 1
+            });
+            #[cfg(not(feature = "cluster_metadata"))]
+            let properties = None;
+
+            next_data.push(ClusterRow {
+                x: cx,
+                y: cy,
+                zoom: None,
+                id,
+                parent: None,
+                count: num_points,
+                weight,
+                partition: data.partition(i),
+                position: data.position(representative),
+                bbox,
+                hash,
+                // Clusters are not part of a multi point
+                part: None,
+                properties,
+            });
+
+            true
+        } else {
+            // Left points as unclustered
+            next_data.push(data.row(i));
+
+            if num_points > 1 {
+                for k in neighbor_ids {
+                    if data.zoom[k] <= zoom as u8 || data.partition[k] != partition {
+                        continue;
+                    }
+
+                    data.zoom[k] = zoom as u8;
+
+                    next_data.push(data.row(k));
+                }
+            }
+
+            false
+        }
+    }
+
+    /// Select the member whose displayed position is inherited by a new cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: The cluster data of the current zoom level.
+    /// - `members`: The indices of the rows of the merged points and clusters, starting with the origin.
+    /// - `x`: The x coordinate of the weighted mean of the cluster.
+    /// - `y`: The y coordinate of the weighted mean of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// The index of the row of the selected member.
+    fn get_representative(&self, data: &ClusterData, members: &[usize], x: f64, y: f64) -> usize {
+        let periodic = is_periodic(&self.options);
+        let distance = |k: usize| {
+            let [px, py, _] = data.position(k);
+            let (dx, dy) = (px + get_x_shift(px, x, periodic) - x, py - y);
+
+            dx * dx + dy * dy
+        };
+
+        // Ties are resolved in favour of the earliest member to keep the result deterministic
+        members
+            .iter()
+            .copied()
+            .reduce(|best, k| {
+                let better = match self.options.position {
+                    ClusterPosition::WeightedMean | ClusterPosition::Seed => false,
+                    ClusterPosition::Medoid => distance(k) < distance(best),
+                    ClusterPosition::HighestWeight => data.position(k)[2] > data.position(best)[2],
+                };
+
+                if better {
+                    k
+                } else {
+                    best
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the properties of a point or a cluster to be aggregated into a parent cluster.
+    /// Points are mapped with the `map` function, clusters return a copy of their aggregated properties.
+    ///
+    /// # Arguments
+    ///
+    /// - `data`: A reference to the cluster data.
+    /// - `i`: The index of the row of the point or cluster.
+    /// - `metadata`: The cluster metadata.
+    ///
+    /// # Returns
+    ///
+    /// The properties to be aggregated.
+    #[cfg(feature = "cluster_metadata")]
+    fn map_properties(&self, data: &ClusterData, i: usize, metadata: &[JsonObject]) -> JsonObject {
+        if data.is_cluster(i) {
+            return get_cluster_properties(data, i, metadata).unwrap_or_default();
+        }
+
+        let properties = self.points[data.id[i] as usize].properties();
+
+        match (&self.options.map, properties) {
+            (Some(map), Some(properties)) => map(properties),
+            (Some(map), None) => map(&JsonObject::new()),
+            (None, properties) => properties.cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Get the index of the point from which the cluster originated.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// The index of the point from which the cluster originated, otherwise an error if the cluster ID is invalid.
+    pub fn get_origin_id(&self, cluster_id: ClusterId) -> Result<usize, SuperclusterError> {
+        cluster_id.origin(self)
+    }
+
+    /// Get the zoom of the point from which the cluster originated.
+    /// The zoom level is encoded in the cluster ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// The zoom level of the point from which the cluster originated, otherwise an error if the cluster ID is invalid.
+    pub fn get_origin_zoom(&self, cluster_id: ClusterId) -> Result<usize, SuperclusterError> {
+        cluster_id.zoom(self)
+    }
+
+    /// Encode the ID of a new cluster.
+    ///
+    /// # Arguments
+    ///
+    /// - `origin_id`: The index in the KD-tree of the point from which the cluster originated.
+    /// - `zoom`: The zoom level at which the cluster is created.
+    ///
+    /// # Returns
+    ///
+    /// The unique identifier of the cluster.
+    fn get_new_cluster_id(&self, origin_id: usize, zoom: usize) -> ClusterId {
+        ClusterId(((origin_id as u64) << self.get_zoom_bits()) + (zoom as u64 + 1) + self.id_offset)
+    }
+
+    /// Check whether the IDs of the clusters originating from the rows fit into `u64`, and the rows can be indexed as `u32`.
+    ///
+    /// # Arguments
+    ///
+    /// - `rows`: The number of rows the clusters may originate from.
+    /// - `id_offset`: The offset of the cluster IDs.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the rows can be clustered, otherwise `false`.
+    fn fits_cluster_ids(&self, rows: usize, id_offset: u64) -> bool {
+        let max_id = ((rows as u128) << self.get_zoom_bits())
+            + (self.options.max_zoom as u128)
+            + 1
+            + id_offset as u128;
+
+        max_id <= u64::MAX as u128 && rows < NO_INDEX as usize
+    }
+
+    /// Remove the offset of the cluster IDs from a cluster ID.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// The encoded origin and zoom of the cluster, otherwise an error if the ID is lower than the offset.
+    fn get_cluster_offset(&self, cluster_id: ClusterId) -> Result<u64, SuperclusterError> {
+        cluster_id
+            .0
+            .checked_sub(self.id_offset)
+            .ok_or(SuperclusterError::InvalidClusterId)
+    }
+
+    /// Get the number of bits encoding the zoom level in the cluster IDs.
+    ///
+    /// # Returns
+    ///
+    /// 5 bits, or more if the maximal zoom level does not fit.
+    fn get_zoom_bits(&self) -> u32 {
+        (u64::BITS - (self.options.max_zoom as u64 + 1).leading_zeros()).max(5)
+    }
+}
+
+impl<T: ClusterPoint<Payload = Feature>> Supercluster<T> {
+    /// Retrieve the convex hull of the leaf features within a cluster.
+    /// The hull encloses every position of the leaf geometries.
+    ///
+    /// # Arguments
+    ///
+    /// - `cluster_id`: The unique identifier of the cluster.
+    ///
+    /// # Returns
+    ///
+    /// A GeoJSON polygon enclosing the leaf features, or a line string if they are collinear and a point if they coincide,
+    /// otherwise an error if the cluster is not found.
+    pub fn get_cluster_hull(&self, cluster_id: ClusterId) -> Result<Geometry, SuperclusterError> {
+        let mut rows = vec![];
+        self.append_leaf_rows(&mut rows, cluster_id, usize::MAX, 0, 0);
+
+        let mut points: Vec<[f64; 2]> = vec![];
+
+        for (data, k) in rows {
+            let point = &self.points[data.id[k] as usize];
+
+            match point
+                .as_feature()
+                .and_then(|feature| feature.geometry.as_ref())
+            {
+                // A row of a multi point holds a single one of its points
+                Some(geometry) => match (&geometry.value, data.part(k)) {
+                    (MultiPoint(positions), Some(part)) => points.extend(
+                        positions
+                            .get(part)
+                            .map(|position| [position[0], position[1]]),
+                    ),
+                    (value, _) => points.extend(get_positions(value)),
+                },
+                None => points.extend(
+                    self.get_leaf(data, k)
+                        .geometry
+                        .iter()
+                        .flat_map(|geometry| get_positions(&geometry.value)),
+                ),
+            }
+        }
+
+        if points.is_empty() {
+            return Err(SuperclusterError::ClusterNotFound);
+        }
+
+        Ok(Geometry::new(convex_hull(points)))
+    }
+
+    /// Retrieve a vector of features within a tile at the given zoom level and tile coordinates.
+    /// The tile is a square area of the map that is rendered as an image.
+    /// The zoom level determines the scale of the map.
+    /// The X and Y coordinates determine the position of the tile on the map.
+    /// The tile is represented by a GeoJSON FeatureCollection.
+    ///
+    /// # Arguments
+    ///
+    /// - `z`: The zoom level of the tile.
+    /// - `x`: The X coordinate of the tile.
+    /// - `y`: The Y coordinate of the tile.
+    ///
+    /// # Returns
+    ///
+    /// A list of GeoJSON features within the specified tile, otherwise an error if the tile is not found.
+    pub fn get_tile(&self, z: u8, x: f64, y: f64) -> Result<FeatureCollection, SuperclusterError> {
+        self.get_source_tile(self, z, x, y)
+    }
+
+    /// Retrieve a vector tile of the cluster hierarchy, whose leaves are input points of a source instance.
+    ///
+    /// # Arguments
+    ///
+    /// - `source`: The instance holding the input points, the hierarchy itself unless it is filtered.
+    /// - `z`: The zoom level of the tile.
+    /// - `x`: The X coordinate of the tile.
+    /// - `y`: The Y coordinate of the tile.
+    ///
+    /// # Returns
+    ///
+    /// A list of GeoJSON features within the specified tile, otherwise an error if the tile is not found.
+    fn get_source_tile(
+        &self,
+        source: &Supercluster<T>,
+        z: u8,
+        x: f64,
+        y: f64,
+    ) -> Result<FeatureCollection, SuperclusterError> {
+        let zoom = self.limit_zoom(z);
+        let tree = match self.trees.get(&zoom) {
             Some(tree) => tree,
             None => {
                 #[cfg(feature = "log")]
@@ -417,8 +2966,16 @@ impl Supercluster {
                 return Err(SuperclusterError::TreeNotFound);
             }
         };
-        let z2: f64 = (2u32).pow(z as u32) as f64;
-        let p = self.options.radius / self.options.extent;
+        let z2: f64 = (2.0_f64).powi(z as i32);
+        let p = match self.options.radius_unit {
+            RadiusUnit::Pixels => self.options.radius_at(z as usize) / self.options.extent,
+            // Pad the tile by the radius at its edge nearest to the pole, where it is the widest
+            RadiusUnit::Meters => {
+                let edge = if 2.0 * y + 1.0 < z2 { y } else { y + 1.0 };
+
+                get_search_radius(&self.options, z as usize, edge / z2) * z2
+            }
+        };
         let top = (y - p) / z2;
         let bottom = (y + 1.0 + p) / z2;
 
@@ -429,16 +2986,18 @@ impl Supercluster {
         };
 
         let ids = tree.range((x - p) / z2, top, (x + 1.0 + p) / z2, bottom);
-        self.add_tile_features(&ids, &tree.data, x, y, z2, &mut tile);
+        self.add_source_tile_features(source, &ids, &tree.data, [x, y], z2, &mut tile);
 
-        if x == 0.0 {
+        let wraps = self.options.coordinate_system.wraps();
+
+        if wraps && x == 0.0 {
             let ids = tree.range(1.0 - p / z2, top, 1.0, bottom);
-            self.add_tile_features(&ids, &tree.data, z2, y, z2, &mut tile);
+            self.add_source_tile_features(source, &ids, &tree.data, [z2, y], z2, &mut tile);
         }
 
-        if x == z2 - 1.0 {
+        if wraps && x == z2 - 1.0 {
             let ids = tree.range(0.0, top, p / z2, bottom);
-            self.add_tile_features(&ids, &tree.data, -1.0, y, z2, &mut tile);
+            self.add_source_tile_features(source, &ids, &tree.data, [-1.0, y], z2, &mut tile);
         }
 
         if tile.features.is_empty() {
@@ -460,136 +3019,32 @@ impl Supercluster {
         Ok(tile)
     }
 
-    /// Determine the zoom level at which a specific cluster expands.
-    /// The cluster expands when it contains more than one child cluster.
-    /// The cluster expands until it reaches the maximum zoom level or contains more than one child cluster.
-    /// The zoom level at which the cluster expands is the zoom level at which the cluster contains more than one child cluster.
-    /// The cluster does not expand if it contains only one child cluster.
-    ///
-    /// # Arguments
-    ///
-    /// - `cluster_id`: The unique identifier of the cluster.
-    ///
-    /// # Returns
-    ///
-    /// The zoom level at which the cluster expands.
-    pub fn get_cluster_expansion_zoom(&self, mut cluster_id: usize) -> usize {
-        let mut expansion_zoom = self.get_origin_zoom(cluster_id) - 1;
-
-        while expansion_zoom <= (self.options.max_zoom as usize) {
-            let children = match self.get_children(cluster_id) {
-                Ok(children) => children,
-                Err(_) => break,
-            };
-
-            expansion_zoom += 1;
-
-            if children.len() != 1 {
-                break;
-            }
-
-            cluster_id = match children[0].property("cluster_id") {
-                Some(property) => match property.as_u64() {
-                    Some(id) => id as usize,
-                    None => break,
-                },
-                None => break,
-            };
-        }
-
-        expansion_zoom
-    }
-
-    /// Appends leaves (features) to the result vector based on the specified criteria.
-    /// The method is used to collect leaves within a cluster based on the specified limit and offset.
-    /// The method is called recursively to collect leaves within child clusters.
-    /// The method is used internally by the `get_leaves` method.
+    /// Retrieve a vector tile of the points matching a predicate.
     ///
     /// # Arguments
     ///
-    /// - `result`: A mutable reference to a vector where leaves will be appended.
-    /// - `cluster_id`: The identifier of the cluster whose leaves are being collected.
-    /// - `limit`: The maximum number of leaves to collect.
-    /// - `offset`: The number of leaves to skip before starting to collect.
-    /// - `skipped`: The current count of skipped leaves, used for tracking the progress.
+    /// - `filter_id`: The identifier of the filter, used to cache the filtered cluster hierarchy.
+    /// - `predicate`: The predicate over the original points selecting the points to cluster.
+    /// - `z`: The zoom level of the tile.
+    /// - `x`: The X coordinate of the tile.
+    /// - `y`: The Y coordinate of the tile.
     ///
     /// # Returns
     ///
-    /// The updated count of skipped leaves after processing the current cluster.
-    pub fn append_leaves(
+    /// A list of GeoJSON features of the matching points within the specified tile, otherwise an error if the tile is not found.
+    pub fn get_tile_filtered<F>(
         &self,
-        result: &mut Vec<Feature>,
-        cluster_id: usize,
-        limit: usize,
-        offset: usize,
-        mut skipped: usize,
-    ) -> usize {
-        let cluster = match self.get_children(cluster_id) {
-            Ok(cluster) => cluster,
-            Err(_) => return skipped,
-        };
-
-        for child in cluster {
-            if child.contains_property("cluster") {
-                if let Some(point_count) = child.property("point_count").and_then(|p| p.as_i64()) {
-                    if skipped + point_count as usize <= offset {
-                        // Skip the whole cluster
-                        skipped += point_count as usize;
-                    } else {
-                        // Enter the cluster
-                        if let Some(cluster_id) =
-                            child.property("cluster_id").and_then(|c| c.as_u64())
-                        {
-                            skipped = self.append_leaves(
-                                result,
-                                cluster_id as usize,
-                                limit,
-                                offset,
-                                skipped,
-                            );
-                        }
-                        // Exit the cluster
-                    }
-                }
-            } else if skipped < offset {
-                // Skip a single point
-                skipped += 1;
-            } else {
-                // Add a single point
-                result.push(child);
-            }
-
-            if result.len() == limit {
-                break;
-            }
-        }
-
-        skipped
-    }
-
-    /// Create a KD-tree using the specified data, which is used for spatial indexing.
-    /// The KD-tree is used to index the input points for clustering.
-    /// The KD-tree is created for the specified data and configured with the specified node size.
-    /// The KD-tree is used to cluster points based on the specified node size.
-    ///
-    /// # Arguments
-    ///
-    /// - `data`: A vector of flat numeric arrays representing point data for the KD-tree.
-    ///
-    /// # Returns
-    ///
-    /// `KDBush` instance with the specified data.
-    pub fn create_tree(&mut self, data: Vec<f64>) -> KDBush {
-        let mut tree = KDBush::new(data.len() / self.stride, self.options.node_size);
-
-        for i in (0..data.len()).step_by(self.stride) {
-            tree.add_point(data[i], data[i + 1]);
-        }
-
-        tree.build_index();
-        tree.data = data;
-
-        tree
+        filter_id: &str,
+        predicate: F,
+        z: u8,
+        x: f64,
+        y: f64,
+    ) -> Result<FeatureCollection, SuperclusterError>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.filtered(filter_id, predicate)?
+            .get_source_tile(self, z, x, y)
     }
 
     /// Populate a tile with features based on the specified point IDs, data, and tile parameters.
@@ -598,7 +3053,7 @@ impl Supercluster {
     /// # Arguments
     ///
     /// - `ids`: A vector of point IDs used for populating the tile.
-    /// - `data`: A reference to the flat numeric arrays representing point data.
+    /// - `data`: A reference to the cluster data of the zoom level.
     /// - `x`: The X coordinate of the tile.
     /// - `y`: The Y coordinate of the tile.
     /// - `z2`: The zoom level multiplied by 2.
@@ -606,76 +3061,104 @@ impl Supercluster {
     pub fn add_tile_features(
         &self,
         ids: &Vec<usize>,
-        data: &[f64],
+        data: &ClusterData,
         x: f64,
         y: f64,
         z2: f64,
         tile: &mut FeatureCollection,
     ) {
-        for i in ids {
-            let k = i * self.stride;
-            let is_cluster = data[k + OFFSET_NUM] > 1.0;
+        self.add_source_tile_features(self, ids, data, [x, y], z2, tile);
+    }
+
+    /// Populate a tile with features of the cluster hierarchy, whose leaves are input points of a source instance.
+    ///
+    /// # Arguments
+    ///
+    /// - `source`: The instance holding the input points, the hierarchy itself unless it is filtered.
+    /// - `ids`: A vector of point IDs used for populating the tile.
+    /// - `data`: A reference to the cluster data of the zoom level.
+    /// - `[x, y]`: The X and Y coordinates of the tile.
+    /// - `z2`: The zoom level multiplied by 2.
+    /// - `tile`: A mutable reference to the `FeatureCollection` to be populated with features.
+    fn add_source_tile_features(
+        &self,
+        source: &Supercluster<T>,
+        ids: &Vec<usize>,
+        data: &ClusterData,
+        [x, y]: [f64; 2],
+        z2: f64,
+        tile: &mut FeatureCollection,
+    ) {
+        // Transform normalized coordinates into tile coordinates
+        let to_tile = |px: f64, py: f64| {
+            vec![
+                (self.options.extent * (px * z2 - x)).round(),
+                (self.options.extent * (py * z2 - y)).round(),
+            ]
+        };
+
+        for &k in ids {
+            let is_cluster = data.is_cluster(k);
 
             let cluster = if is_cluster {
+                let (cx, cy) = get_cluster_position(data, k, &self.options);
+
                 (
-                    data[k],
-                    data[k + 1],
+                    Geometry::new(Point(to_tile(cx, cy))),
+                    Some(get_cluster_feature_id(data, k, &self.options)),
                     #[cfg(feature = "cluster_metadata")]
-                    get_cluster_metadata(data, k, &self.metadata),
+                    get_cluster_metadata(data, k, &self.options, &self.metadata, &self.partitions),
                 )
             } else {
-                let p = &self.points[data[k + OFFSET_ID] as usize];
+                // Borrow the original feature, unless the point is part of a multi point
+                let leaf = match source.points[data.id[k] as usize].as_feature() {
+                    Some(feature) if data.part(k).is_none() => Cow::Borrowed(feature),
+                    _ => Cow::Owned(source.get_leaf(data, k)),
+                };
 
                 #[cfg(feature = "cluster_metadata")]
-                let properties = match p.properties.as_ref() {
+                let properties = match &leaf.properties {
                     Some(properties) => properties.to_owned(),
                     None => continue, // Handle the case where properties is None
                 };
 
-                let (px, py) = match p.geometry.as_ref() {
-                    Some(geometry) => {
-                        if let Point(coordinates) = &geometry.value {
-                            match &self.options.coordinate_system {
-                                CoordinateSystem::Cartesian { range } => (
-                                    range.normalize(coordinates[0]),
-                                    range.normalize(coordinates[1]),
-                                ),
-                                CoordinateSystem::LatLng => (
-                                    convert_longitude_to_spherical_mercator(coordinates[0]),
-                                    convert_latitude_to_spherical_mercator(coordinates[1]),
-                                ),
-                            }
-                        } else {
-                            continue;
-                        }
-                    }
+                // Unclustered features keep their original geometry
+                let geometry = match &leaf.geometry {
+                    Some(geometry) => Geometry::new(map_positions(&geometry.value, &|position| {
+                        let (px, py) = self.project(position);
+
+                        to_tile(px, py)
+                    })),
                     None => continue, // Handle the case where geometry is None
                 };
 
                 (
-                    px,
-                    py,
+                    geometry,
+                    leaf.id.to_owned(),
                     #[cfg(feature = "cluster_metadata")]
                     properties,
                 )
             };
 
-            let id = if is_cluster {
-                Some(Id::String(data[k + OFFSET_ID].to_string()))
+            // Bounding box of the cluster in tile coordinates
+            let bbox = if is_cluster && self.options.cluster_bbox {
+                let [min_x, min_y, max_x, max_y] = data.bbox(k);
+
+                Some(vec![
+                    (self.options.extent * (min_x * z2 - x)).round(),
+                    (self.options.extent * (min_y * z2 - y)).round(),
+                    (self.options.extent * (max_x * z2 - x)).round(),
+                    (self.options.extent * (max_y * z2 - y)).round(),
+                ])
             } else {
-                self.points[data[k + OFFSET_ID] as usize].id.to_owned()
+                None
             };
 
-            let geometry = Geometry::new(Point(vec![
-                (self.options.extent * (cluster.0 * z2 - x)).round(),
-                (self.options.extent * (cluster.1 * z2 - y)).round(),
-            ]));
-
             tile.features.push(Feature {
-                id,
-                bbox: None,
+                id: cluster.1,
+                bbox,
                 foreign_members: None,
-                geometry: Some(geometry),
+                geometry: Some(cluster.0),
                 #[cfg(feature = "cluster_metadata")]
                 properties: Some(cluster.2),
                 #[cfg(not(feature = "cluster_metadata"))]
@@ -683,241 +3166,555 @@ impl Supercluster {
             });
         }
     }
+}
 
-    /// Calculate the effective zoom level that takes into account the configured minimum and maximum zoom levels.
-    /// The effective zoom level is the zoom level that is within the configured minimum and maximum zoom levels.
-    ///
-    /// # Arguments
-    ///
-    /// - `zoom`: The initial zoom level.
-    ///
-    /// # Returns
-    ///
-    /// The effective zoom level considering the configured minimum and maximum zoom levels.
-    pub fn limit_zoom(&self, zoom: u8) -> usize {
-        #[cfg(feature = "log")]
-        log::debug!("Limiting zoom level to {}", zoom);
+/// Convert clustered point data into a cluster returned from the queries.
+///
+/// # Arguments
+///
+/// - `data`: A reference to the cluster data.
+/// - `i`: The index of the row of the cluster.
+/// - `options`: The configuration options used for clustering.
+/// - `metadata`: The cluster metadata.
+/// - `partitions`: The partition keys.
+///
+/// # Returns
+///
+/// A cluster with its position, counts and properties.
+fn get_cluster(
+    data: &ClusterData,
+    i: usize,
+    options: &SuperclusterOptions,
+    #[cfg(feature = "cluster_metadata")] metadata: &[JsonObject],
+    partitions: &[String],
+) -> Cluster {
+    let (x, y) = get_cluster_position(data, i, options);
+    let coords = match &options.coordinate_system {
+        CoordinateSystem::Cartesian { range } => range.denormalize_point([x, y]),
+        CoordinateSystem::LatLng => [
+            convert_spherical_mercator_to_longitude(x),
+            convert_spherical_mercator_to_latitude(y),
+        ],
+        CoordinateSystem::Custom(projection) => projection.inverse([x, y]),
+    };
 
-        zoom.max(self.options.min_zoom)
-            .min(self.options.max_zoom + 1) as usize
+    let [min_x, min_y, max_x, max_y] = data.bbox(i);
+    let bbox = match &options.coordinate_system {
+        _ if !options.cluster_bbox => None,
+        CoordinateSystem::Cartesian { range } => {
+            let [min_x, min_y] = range.denormalize_point([min_x, min_y]);
+            let [max_x, max_y] = range.denormalize_point([max_x, max_y]);
+
+            Some([min_x, min_y, max_x, max_y])
+        }
+        // The y axis of the spherical mercator points south, and a bounding box across the antimeridian
+        // has a western bound greater than its eastern bound
+        CoordinateSystem::LatLng => Some([
+            wrap_coordinate(
+                convert_spherical_mercator_to_longitude(min_x),
+                [-180.0, 180.0],
+            ),
+            convert_spherical_mercator_to_latitude(max_y),
+            wrap_coordinate(
+                convert_spherical_mercator_to_longitude(max_x),
+                [-180.0, 180.0],
+            ),
+            convert_spherical_mercator_to_latitude(min_y),
+        ]),
+        CoordinateSystem::Custom(projection) => {
+            let mut bbox = projection.inverse_bbox([min_x, min_y, max_x, max_y]);
+
+            if let Some(wrap) = projection.wrap() {
+                bbox[0] = wrap_coordinate(bbox[0], wrap);
+                bbox[2] = wrap_coordinate(bbox[2], wrap);
+            }
+
+            Some(bbox)
+        }
+    };
+
+    Cluster {
+        id: ClusterId(data.id[i]),
+        feature_id: get_cluster_feature_id(data, i, options),
+        count: data.count[i] as usize,
+        weight: data.weight[i],
+        coords,
+        bbox,
+        partition: data
+            .partition(i)
+            .map(|partition| partitions[partition].to_owned()),
+        #[cfg(feature = "cluster_metadata")]
+        properties: Some(get_cluster_metadata(data, i, options, metadata, partitions)),
+        #[cfg(not(feature = "cluster_metadata"))]
+        properties: None,
     }
+}
 
-    /// Cluster points on a given zoom level using a KD-tree and returns updated data arrays.
-    ///
-    /// # Arguments
-    ///
-    /// - `tree`: A reference to the KD-tree structure for spatial indexing.
-    /// - `zoom`: The zoom level at which clustering is performed.
-    ///
-    /// # Returns
-    ///
-    /// A tuple of two vectors: the first one contains updated data arrays for the current zoom level,
-    /// and the second one contains data arrays for the next zoom level.
-    pub fn cluster(&self, tree: &KDBush, zoom: usize) -> (Vec<f64>, Vec<f64>) {
-        let r = self.options.radius / (self.options.extent * (2.0_f64).powi(zoom as i32));
+/// Retrieve the feature ID of a cluster according to the cluster ID mode.
+///
+/// # Arguments
+///
+/// - `data`: A reference to the cluster data.
+/// - `i`: The index of the row of the cluster.
+/// - `options`: The configuration options used for clustering.
+///
+/// # Returns
+///
+/// The internal ID of the cluster, or the hexadecimal stable ID of the cluster.
+fn get_cluster_feature_id(data: &ClusterData, i: usize, options: &SuperclusterOptions) -> Id {
+    match options.id_mode {
+        ClusterIdMode::Index => Id::String(data.id[i].to_string()),
+        ClusterIdMode::Stable => Id::String(format!("{:016x}", get_stable_id(data, i))),
+    }
+}
+
+/// Compute the stable ID of a cluster from the hash of its members.
+///
+/// # Arguments
+///
+/// - `data`: A reference to the cluster data.
+/// - `i`: The index of the row of the cluster.
+///
+/// # Returns
+///
+/// The stable ID of the cluster.
+fn get_stable_id(data: &ClusterData, i: usize) -> u64 {
+    XxHash64::oneshot(0, &data.hash(i).to_le_bytes())
+}
+
+/// Compute the hash identifying an input point, from its GeoJSON ID or otherwise its coordinates.
+///
+/// # Arguments
+///
+/// - `id`: The GeoJSON ID of the point, if any.
+/// - `coordinates`: The coordinates of the point.
+/// - `part`: The index of the point within a multi point, otherwise `None`.
+///
+/// # Returns
+///
+/// The hash of the point.
+fn get_point_hash(id: Option<&Id>, coordinates: &[f64], part: Option<usize>) -> u64 {
+    let mut bytes = match id {
+        Some(id) => get_id_key(id).into_bytes(),
+        None => coordinates
+            .iter()
+            .flat_map(|coordinate| coordinate.to_le_bytes())
+            .collect(),
+    };
+
+    // Tell apart the points of a multi point sharing the same ID
+    if let Some(part) = part {
+        bytes.extend(b"p");
+        bytes.extend((part as u64).to_le_bytes());
+    }
+
+    XxHash64::oneshot(0, &bytes)
+}
+
+/// Tag a GeoJSON ID with its type, so string and number IDs with the same digits are told apart.
+///
+/// # Arguments
+///
+/// - `id`: The GeoJSON ID.
+///
+/// # Returns
+///
+/// The ID prefixed with `s` for a string or `n` for a number.
+fn get_id_key(id: &Id) -> String {
+    match id {
+        Id::String(id) => format!("s{}", id),
+        Id::Number(id) => format!("n{}", id),
+    }
+}
+
+/// Get the tagged GeoJSON ID of an input point.
+///
+/// # Arguments
+///
+/// - `point`: The input point.
+///
+/// # Returns
+///
+/// The tagged GeoJSON ID, otherwise `None` if the point is not a feature or has no ID.
+fn get_point_id_key<T: ClusterPoint>(point: &T) -> Option<String> {
+    point
+        .as_feature()
+        .and_then(|feature| feature.id.as_ref())
+        .map(get_id_key)
+}
 
-        #[cfg(feature = "log")]
-        log::debug!("Clustering points at zoom level {}", zoom);
+/// Retrieve the displayed position of a cluster according to the cluster position option.
+///
+/// # Arguments
+///
+/// - `data`: A reference to the cluster data.
+/// - `i`: The index of the row of the cluster.
+/// - `options`: The configuration options used for clustering.
+///
+/// # Returns
+///
+/// The normalized x and y coordinates at which the cluster is displayed.
+fn get_cluster_position(data: &ClusterData, i: usize, options: &SuperclusterOptions) -> (f64, f64) {
+    match options.position {
+        ClusterPosition::WeightedMean => {
+            let [x, y] = data.coords(i);
 
-        let mut data = tree.data.to_owned();
-        let mut next_data = vec![];
+            (x, y)
+        }
+        _ => {
+            let [x, y, _] = data.position(i);
 
-        // Loop through each point
-        for i in (0..data.len()).step_by(self.stride) {
-            // If we've already visited the point at this zoom level, skip it
-            if data[i + OFFSET_ZOOM] <= (zoom as f64) {
-                continue;
-            }
+            (x, y)
+        }
+    }
+}
 
-            data[i + OFFSET_ZOOM] = zoom as f64;
+/// Retrieve metadata for a cluster based on clustered point data.
+///
+/// # Arguments
+///
+/// - `data`: A reference to the cluster data.
+/// - `i`: The index of the row of the cluster.
+/// - `options`: The configuration options used for clustering.
+/// - `metadata`: The cluster metadata.
+/// - `partitions`: The partition keys.
+///
+/// # Returns
+///
+/// Metadata for the cluster based on the clustered point data.
+#[cfg(feature = "cluster_metadata")]
+fn get_cluster_metadata(
+    data: &ClusterData,
+    i: usize,
+    options: &SuperclusterOptions,
+    metadata: &[JsonObject],
+    partitions: &[String],
+) -> JsonObject {
+    let count = data.count[i] as f64;
+    let abbrev = if count >= 10000.0 {
+        format!("{}k", count / 1000.0)
+    } else if count >= 1000.0 {
+        format!("{:}k", count / 100.0 / 10.0)
+    } else {
+        count.to_string()
+    };
 
-            // Find all nearby points
-            let x = data[i];
-            let y = data[i + 1];
+    let mut properties = get_cluster_properties(data, i, metadata).unwrap_or_default();
 
-            let neighbor_ids = tree.within(x, y, r);
+    properties.insert("cluster".to_string(), json!(true));
+    properties.insert("cluster_id".to_string(), json!(data.id[i]));
+    properties.insert("point_count".to_string(), json!(count as usize));
+    properties.insert("point_count_abbreviated".to_string(), json!(abbrev));
 
-            let num_points_origin = data[i + OFFSET_NUM];
-            let mut num_points = num_points_origin;
+    if options.weight.is_some() {
+        properties.insert("point_weight".to_string(), json!(data.weight[i]));
+    }
 
-            // Count the number of points in a potential cluster
-            for neighbor_id in &neighbor_ids {
-                let k = neighbor_id * self.stride;
+    if let Some(partition) = data
+        .partition(i)
+        .and_then(|partition| partitions.get(partition))
+    {
+        properties.insert("partition_key".to_string(), json!(partition));
+    }
 
-                // Filter out neighbors that are already processed
-                if data[k + OFFSET_ZOOM] > (zoom as f64) {
-                    num_points += data[k + OFFSET_NUM];
-                }
-            }
+    properties
+}
 
-            // If there were neighbors to merge, and there are enough points to form a cluster
-            if num_points > num_points_origin && num_points >= (self.options.min_points as f64) {
-                let mut wx = x * num_points_origin;
-                let mut wy = y * num_points_origin;
+/// Retrieve the aggregated properties of a cluster, if any.
+///
+/// # Arguments
+///
+/// - `data`: A reference to the cluster data.
+/// - `i`: The index of the row of the cluster.
+/// - `metadata`: The cluster metadata.
+///
+/// # Returns
+///
+/// A copy of the aggregated cluster properties, otherwise `None` if the properties are not aggregated.
+#[cfg(feature = "cluster_metadata")]
+fn get_cluster_properties(
+    data: &ClusterData,
+    i: usize,
+    metadata: &[JsonObject],
+) -> Option<JsonObject> {
+    data.properties(i)
+        .and_then(|index| metadata.get(index))
+        .cloned()
+}
 
-                // Encode both zoom and point index on which the cluster originated -- offset by total length of features
-                let id = ((i / self.stride) << 5) + (zoom + 1) + self.points.len();
+/// Compute the convex hull of a set of points using the monotone chain algorithm.
+///
+/// # Arguments
+///
+/// - `points`: The points to enclose.
+///
+/// # Returns
+///
+/// A polygon with the closed counter-clockwise ring of the hull vertices,
+/// otherwise a line string between the extreme points if they are collinear, or a point if they coincide.
+fn convex_hull(mut points: Vec<[f64; 2]>) -> Value {
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+
+    let hull = if points.len() < 3 {
+        points.clone()
+    } else {
+        let cross = |o: &[f64; 2], a: &[f64; 2], b: &[f64; 2]| {
+            (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+        };
+        let half = |points: &mut dyn Iterator<Item = &[f64; 2]>| {
+            let mut half: Vec<[f64; 2]> = vec![];
+
+            for point in points {
+                while half.len() >= 2
+                    && cross(&half[half.len() - 2], &half[half.len() - 1], point) <= 0.0
+                {
+                    half.pop();
+                }
 
-                for neighbor_id in neighbor_ids {
-                    let k = neighbor_id * self.stride;
+                half.push(*point);
+            }
 
-                    if data[k + OFFSET_ZOOM] <= (zoom as f64) {
-                        continue;
-                    }
+            // The last point of each half is the first point of the other half
+            half.pop();
+            half
+        };
 
-                    // Save the zoom (so it doesn't get processed twice)
-                    data[k + OFFSET_ZOOM] = zoom as f64;
+        let mut hull = half(&mut points.iter());
+        hull.extend(half(&mut points.iter().rev()));
+        hull
+    };
 
-                    let num_points2 = data[k + OFFSET_NUM];
+    let mut ring: Vec<Vec<f64>> = hull.into_iter().map(|point| point.to_vec()).collect();
 
-                    // Accumulate coordinates for calculating weighted center
-                    wx += data[k] * num_points2;
-                    wy += data[k + 1] * num_points2;
+    match ring.len() {
+        0 | 1 => Point(ring.pop().unwrap_or_default()),
+        2 => LineString(ring),
+        _ => {
+            ring.push(ring[0].clone());
 
-                    data[k + OFFSET_PARENT] = id as f64;
-                }
+            Polygon(vec![ring])
+        }
+    }
+}
 
-                data[i + OFFSET_PARENT] = id as f64;
+/// Check whether the x axis of the clustering space is periodic, so points on both sides of its edges are clustered together.
+/// Longitudes wrap around at the antimeridian, while Cartesian coordinates never do.
+///
+/// # Arguments
+///
+/// - `options`: The configuration options used for clustering.
+///
+/// # Returns
+///
+/// `true` if the x axis wraps around from 1 to 0 during clustering, otherwise `false`.
+fn is_periodic(options: &SuperclusterOptions) -> bool {
+    options.wrap_clusters
+        && match &options.coordinate_system {
+            CoordinateSystem::LatLng => true,
+            CoordinateSystem::Cartesian { .. } => false,
+            CoordinateSystem::Custom(projection) => projection.wrap().is_some(),
+        }
+}
 
-                next_data.push(wx / num_points);
-                next_data.push(wy / num_points);
-                next_data.push(f64::INFINITY);
-                next_data.push(id as f64);
-                next_data.push(-1.0);
-                next_data.push(num_points);
-            } else {
-                // Left points as unclustered
-                for j in 0..self.stride {
-                    next_data.push(data[i + j]);
-                }
+/// Get the shift moving an x coordinate next to a reference x coordinate.
+/// On a periodic axis, the x coordinate is moved by whole axis lengths across the edges of the axis when it is nearer.
+///
+/// # Arguments
+///
+/// - `x`: The normalized x coordinate.
+/// - `origin`: The normalized reference x coordinate.
+/// - `periodic`: Whether the x axis wraps around from 1 to 0.
+///
+/// # Returns
+///
+/// The shift to add to the x coordinate, `0.0` unless the x coordinate is nearer across an edge.
+fn get_x_shift(x: f64, origin: f64, periodic: bool) -> f64 {
+    if periodic {
+        -(x - origin).round()
+    } else {
+        0.0
+    }
+}
 
-                if num_points > 1.0 {
-                    for neighbor_id in neighbor_ids {
-                        let k = neighbor_id * self.stride;
+/// Wrap a coordinate outside a wrapping range back into the range.
+///
+/// # Arguments
+///
+/// - `v`: The coordinate value.
+/// - `wrap`: The lower and upper bounds of the wrapping range.
+///
+/// # Returns
+///
+/// The coordinate value within the range.
+fn wrap_coordinate(v: f64, wrap: [f64; 2]) -> f64 {
+    let [lower, upper] = wrap;
+
+    if v < lower {
+        v + (upper - lower)
+    } else if v > upper {
+        v - (upper - lower)
+    } else {
+        v
+    }
+}
 
-                        if data[k + OFFSET_ZOOM] <= (zoom as f64) {
-                            continue;
-                        }
+/// Split a query bounding box at the edges of a wrapping range of x coordinates.
+///
+/// # Arguments
+///
+/// - `bbox`: The bounding box of the query as `[min_x, min_y, max_x, max_y]`.
+/// - `wrap`: The western and eastern bounds of the wrapping x coordinates.
+///
+/// # Returns
+///
+/// The bounding boxes within the wrapping range covering the query.
+fn get_wrapped_bboxes(bbox: [f64; 4], wrap: [f64; 2]) -> Vec<[f64; 4]> {
+    let [west, east] = wrap;
+    let period = east - west;
 
-                        data[k + OFFSET_ZOOM] = zoom as f64;
+    if bbox[2] - bbox[0] >= period {
+        return vec![[west, bbox[1], east, bbox[3]]];
+    }
 
-                        for j in 0..self.stride {
-                            next_data.push(data[k + j]);
-                        }
-                    }
-                }
-            }
-        }
+    let normalize = |x: f64| ((x - west) % period + period) % period + west;
+    let min_x = normalize(bbox[0]);
+    let max_x = if bbox[2] == east {
+        east
+    } else {
+        normalize(bbox[2])
+    };
 
-        (data, next_data)
+    if min_x > max_x {
+        vec![
+            [min_x, bbox[1], east, bbox[3]],
+            [west, bbox[1], max_x, bbox[3]],
+        ]
+    } else {
+        vec![[min_x, bbox[1], max_x, bbox[3]]]
     }
+}
 
-    /// Get the index of the point from which the cluster originated.
-    ///
-    /// # Arguments
-    ///
-    /// - `cluster_id`: The unique identifier of the cluster.
-    ///
-    /// # Returns
-    ///
-    /// The index of the point from which the cluster originated.
-    pub fn get_origin_id(&self, cluster_id: usize) -> usize {
-        (cluster_id - self.points.len()) >> 5
-    }
+/// Get the grid cell of a row, within its partition.
+/// On a periodic x axis, the columns wrap around, so the rows on both edges of the axis share a cell.
+///
+/// # Arguments
+///
+/// - `data`: The cluster data of the zoom level.
+/// - `k`: The index of the row.
+/// - `r`: The size of the grid cells in normalized coordinates.
+/// - `periodic`: Whether the x axis wraps around from 1 to 0.
+///
+/// # Returns
+///
+/// The column, the row and the partition of the cell.
+fn get_cell(data: &ClusterData, k: usize, r: f64, periodic: bool) -> (i64, i64, i64) {
+    let [x, y] = data.coords(k);
+    let mut column = (x / r).floor() as i64;
 
-    /// Get the zoom of the point from which the cluster originated.
-    /// The zoom level is encoded in the cluster ID.
-    ///
-    /// # Arguments
-    ///
-    /// - `cluster_id`: The unique identifier of the cluster.
-    ///
-    /// # Returns
-    ///
-    /// The zoom level of the point from which the cluster originated.
-    pub fn get_origin_zoom(&self, cluster_id: usize) -> usize {
-        (cluster_id - self.points.len()) % 32
+    if periodic {
+        column = column.rem_euclid((1.0 / r).ceil().max(1.0) as i64);
     }
+
+    (column, (y / r).floor() as i64, data.partition[k] as i64)
 }
 
-/// Convert clustered point data into a GeoJSON feature representing a cluster.
+/// Number of points whose neighbors are searched in parallel before they are merged,
+/// which bounds the memory holding the neighbors.
+#[cfg(feature = "rayon")]
+const NEIGHBORS_CHUNK_SIZE: usize = 4096;
+
+/// Radius of the sphere of the spherical mercator projection, in meters.
+const EARTH_RADIUS: f64 = 6378137.0;
+
+/// Find the neighbors of a point or a cluster within the cluster radius.
 ///
 /// # Arguments
 ///
-/// - `data`: A reference to the flat numeric arrays representing point data.
-/// - `i`: The index in the data array for the cluster.
-/// - `coordinate_system`: The coordinate system used for clustering.
-/// - `metadata`: The cluster metadata.
+/// - `options`: The configuration options used for clustering.
+/// - `tree`: A reference to the KD-tree of the zoom level.
+/// - `data`: A reference to the cluster data of the zoom level.
+/// - `i`: The index of the row of the point or cluster.
+/// - `zoom`: The zoom level at which clustering is performed.
 ///
 /// # Returns
 ///
-/// A GeoJSON feature representing a cluster.
-fn get_cluster(
-    data: &[f64],
+/// The indices of the rows of the neighbors, including the point itself.
+fn get_neighbors(
+    options: &SuperclusterOptions,
+    tree: &KDBush,
+    data: &ClusterData,
     i: usize,
-    coordinate_system: &CoordinateSystem,
-    #[cfg(feature = "cluster_metadata")] metadata: &[JsonObject],
-) -> Feature {
-    let geometry = match coordinate_system {
-        CoordinateSystem::Cartesian { range } => Geometry::new(Point(vec![
-            range.denormalize(data[i]),
-            range.denormalize(data[i + 1]),
-        ])),
-        CoordinateSystem::LatLng => Geometry::new(Point(vec![
-            convert_spherical_mercator_to_longitude(data[i]),
-            convert_spherical_mercator_to_latitude(data[i + 1]),
-        ])),
+    zoom: usize,
+) -> Vec<usize> {
+    let [x, y] = data.coords(i);
+    let r = get_search_radius(options, zoom, y);
+    let ids = if is_periodic(options) {
+        tree.within_wrapped(x, y, r)
+    } else {
+        tree.within(x, y, r)
     };
 
-    Feature {
-        id: Some(Id::String(data[i + OFFSET_ID].to_string())),
-        bbox: None,
-        foreign_members: None,
-        geometry: Some(geometry),
-        #[cfg(feature = "cluster_metadata")]
-        properties: Some(get_cluster_metadata(data, i, metadata)),
-        #[cfg(not(feature = "cluster_metadata"))]
-        properties: None,
+    match options.radius_unit {
+        RadiusUnit::Pixels => ids,
+        RadiusUnit::Meters => {
+            let radius = options.radius_at(zoom);
+
+            ids.into_iter()
+                .filter(|k| get_distance([x, y], data.coords(*k)) <= radius)
+                .collect()
+        }
     }
 }
 
-/// Retrieve metadata for a cluster based on clustered point data.
+/// Get the radius within which the neighbors of a point are searched in the KD-tree.
 ///
 /// # Arguments
 ///
-/// - `data`: A reference to the flat numeric arrays representing point data.
-/// - `i`: The index in the data array for the cluster.
-/// - `metadata`: The cluster metadata.
+/// - `options`: The configuration options used for clustering.
+/// - `zoom`: The zoom level at which clustering is performed.
+/// - `y`: The normalized y coordinate of the point.
 ///
 /// # Returns
 ///
-/// Metadata for the cluster based on the clustered point data.
-#[cfg(feature = "cluster_metadata")]
-fn get_cluster_metadata(data: &[f64], i: usize, metadata: &[JsonObject]) -> JsonObject {
-    let count = data[i + OFFSET_NUM];
-    let abbrev = if count >= 10000.0 {
-        format!("{}k", count / 1000.0)
-    } else if count >= 1000.0 {
-        format!("{:}k", count / 100.0 / 10.0)
-    } else {
-        count.to_string()
-    };
+/// The search radius in normalized coordinates. A radius in meters is converted at the latitude
+/// on the poleward side of the point, so it covers every point within the radius on the ground.
+fn get_search_radius(options: &SuperclusterOptions, zoom: usize, y: f64) -> f64 {
+    let radius = options.radius_at(zoom);
+
+    match options.radius_unit {
+        RadiusUnit::Pixels => radius / (options.extent * (2.0_f64).powi(zoom as i32)),
+        RadiusUnit::Meters => {
+            // The spherical mercator scale grows from 1 at the equator towards the poles
+            let circumference = 2.0 * PI * EARTH_RADIUS;
+            let scale = |y: f64| (PI * (1.0 - 2.0 * y.clamp(0.0, 1.0))).cosh();
+            let r = radius * scale(y) / circumference;
+
+            radius * scale(y - 2.0 * r).max(scale(y + 2.0 * r)) / circumference
+        }
+    }
+}
 
-    let mut properties = if !metadata.is_empty() && data.get(i + OFFSET_PROP).is_some() {
-        metadata[data[i + OFFSET_PROP] as usize].to_owned()
-    } else {
-        JsonObject::new()
-    };
+/// Compute the great-circle distance between two points.
+///
+/// # Arguments
+///
+/// - `a`: The normalized spherical mercator coordinates of the first point.
+/// - `b`: The normalized spherical mercator coordinates of the second point.
+///
+/// # Returns
+///
+/// The distance between the points on the ground, in meters.
+fn get_distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    let lng1 = convert_spherical_mercator_to_longitude(a[0]).to_radians();
+    let lat1 = convert_spherical_mercator_to_latitude(a[1]).to_radians();
+    let lng2 = convert_spherical_mercator_to_longitude(b[0]).to_radians();
+    let lat2 = convert_spherical_mercator_to_latitude(b[1]).to_radians();
 
-    properties.insert("cluster".to_string(), json!(true));
-    properties.insert(
-        "cluster_id".to_string(),
-        json!(data[i + OFFSET_ID] as usize),
-    );
-    properties.insert("point_count".to_string(), json!(count as usize));
-    properties.insert("point_count_abbreviated".to_string(), json!(abbrev));
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lng2 - lng1) / 2.0).sin().powi(2);
 
-    properties
+    2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
 }
 
 /// Convert longitude to spherical mercator in the [0..1] range.
@@ -983,14 +3780,14 @@ mod tests {
     use geojson::JsonObject;
 
     fn setup() -> Supercluster {
-        let options = Supercluster::builder().build();
+        let options = Supercluster::builder().build().unwrap();
         Supercluster::new(options)
     }
 
     #[test]
     fn test_builder() {
-        let options = Supercluster::builder().build();
-        let supercluster = Supercluster::new(options);
+        let options = Supercluster::builder().build().unwrap();
+        let supercluster: Supercluster = Supercluster::new(options);
 
         assert_eq!(supercluster.options.min_zoom, 0);
         assert_eq!(supercluster.options.max_zoom, 16);
@@ -1015,6 +3812,34 @@ mod tests {
         assert_eq!(feature.geometry, Some(Geometry::new(Point(vec![0.0, 0.0]))));
     }
 
+    #[test]
+    fn test_find_point_with_duplicate_ids() {
+        let mut supercluster = setup();
+        let id = Id::String("a".to_string());
+        let feature = |x: f64| Feature {
+            id: Some(id.clone()),
+            geometry: Some(Geometry::new(Point(vec![x, 0.0]))),
+            ..Default::default()
+        };
+
+        supercluster
+            .load(vec![feature(0.0), feature(10.0), feature(20.0)])
+            .unwrap();
+
+        assert_eq!(supercluster.find_point(&id), Some(0));
+        assert_eq!(supercluster.find_point(&Id::Number(0.into())), None);
+
+        supercluster.remove(0).unwrap();
+
+        assert_eq!(supercluster.find_point(&id), Some(1));
+
+        supercluster.remove(1).unwrap();
+        supercluster.remove(0).unwrap();
+
+        assert_eq!(supercluster.find_point(&id), None);
+        assert!(supercluster.point_ids.is_empty());
+    }
+
     #[test]
     fn test_limit_zoom() {
         let supercluster = setup();
@@ -1026,20 +3851,87 @@ mod tests {
     fn test_get_origin_id() {
         let supercluster = setup();
 
-        assert_eq!(supercluster.get_origin_id(100), 3);
+        assert_eq!(supercluster.get_origin_id(ClusterId(100)), Ok(3));
     }
 
     #[test]
     fn test_get_origin_zoom() {
         let supercluster = setup();
 
-        assert_eq!(supercluster.get_origin_zoom(100), 4);
+        assert_eq!(supercluster.get_origin_zoom(ClusterId(100)), Ok(4));
+    }
+
+    #[test]
+    fn test_invalid_cluster_id() {
+        let mut supercluster = setup();
+        supercluster.id_offset = 10;
+
+        assert_eq!(
+            supercluster.get_origin_id(ClusterId(5)),
+            Err(SuperclusterError::InvalidClusterId)
+        );
+        assert_eq!(
+            supercluster.get_origin_zoom(ClusterId(42)),
+            Err(SuperclusterError::InvalidClusterId)
+        );
+        assert_eq!(
+            supercluster.get_cluster_expansion_zoom(ClusterId(5)),
+            Err(SuperclusterError::InvalidClusterId)
+        );
+    }
+
+    #[test]
+    fn test_cluster_id_zoom_bits() {
+        let mut supercluster = setup();
+        supercluster.id_offset = 10;
+
+        assert_eq!(supercluster.get_zoom_bits(), 5);
+        assert_eq!(supercluster.get_new_cluster_id(3, 4), ClusterId(111));
+
+        supercluster.options.max_zoom = 40;
+        let cluster_id = supercluster.get_new_cluster_id(3, 35);
+
+        assert_eq!(supercluster.get_zoom_bits(), 6);
+        assert_eq!(supercluster.get_origin_id(cluster_id), Ok(3));
+        assert_eq!(supercluster.get_origin_zoom(cluster_id), Ok(36));
+        assert_eq!(cluster_id.origin(&supercluster), Ok(3));
+        assert_eq!(cluster_id.zoom(&supercluster), Ok(36));
+        assert_eq!(
+            ClusterId(5).origin(&supercluster),
+            Err(SuperclusterError::InvalidClusterId)
+        );
+    }
+
+    #[cfg(feature = "cluster_metadata")]
+    fn cluster_data(count: u32) -> ClusterData {
+        let mut data = ClusterData::new(Columns {
+            properties: true,
+            ..Default::default()
+        });
+
+        data.push(ClusterRow {
+            x: 0.0,
+            y: 0.0,
+            zoom: Some(0),
+            id: 0,
+            parent: Some(0),
+            count,
+            weight: count as f64,
+            partition: None,
+            position: [0.0, 0.0, 1.0],
+            bbox: [0.0; 4],
+            hash: 0,
+            part: None,
+            properties: Some(0),
+        });
+
+        data
     }
 
     #[test]
     #[cfg(feature = "cluster_metadata")]
     fn test_get_cluster_with_metadata() {
-        let data = [0.0, 0.0, 0.0, 0.0, 0.0, 3.0, 0.0];
+        let data = cluster_data(3);
         let i = 0;
         let mut metadata = JsonObject::new();
 
@@ -1052,7 +3944,13 @@ mod tests {
             serde_json::json!("0".to_string()),
         );
 
-        let result = get_cluster(&data, i, &CoordinateSystem::LatLng, &[metadata]);
+        let result = Feature::from(get_cluster(
+            &data,
+            i,
+            &Supercluster::builder().build().unwrap(),
+            &[metadata],
+            &[],
+        ));
 
         assert_eq!(result.id, Some(Id::String("0".to_string())));
 
@@ -1086,11 +3984,17 @@ mod tests {
     #[test]
     #[cfg(feature = "cluster_metadata")]
     fn test_get_cluster_without_metadata() {
-        let data = [0.0, 0.0, 0.0, 0.0, 0.0, 3.0, 0.0];
+        let data = cluster_data(3);
         let i = 0;
         let metadata = vec![];
 
-        let result = get_cluster(&data, i, &CoordinateSystem::LatLng, &metadata);
+        let result = Feature::from(get_cluster(
+            &data,
+            i,
+            &Supercluster::builder().build().unwrap(),
+            &metadata,
+            &[],
+        ));
 
         assert_eq!(result.id, Some(Id::String("0".to_string())));
 
@@ -1126,7 +4030,7 @@ mod tests {
     #[test]
     #[cfg(feature = "cluster_metadata")]
     fn test_get_cluster_metadata_with_metadata() {
-        let data = [0.0, 0.0, 0.0, 0.0, 0.0, 10000.0, 0.0];
+        let data = cluster_data(10000);
         let i = 0;
         let mut metadata = JsonObject::new();
 
@@ -1139,7 +4043,13 @@ mod tests {
             serde_json::json!("0".to_string()),
         );
 
-        let result = get_cluster_metadata(&data, i, &[metadata]);
+        let result = get_cluster_metadata(
+            &data,
+            i,
+            &Supercluster::builder().build().unwrap(),
+            &[metadata],
+            &[],
+        );
 
         assert!(result.get("cluster").unwrap().as_bool().unwrap());
         assert_eq!(result.get("cluster_id").unwrap().as_i64().unwrap(), 0);
@@ -1161,11 +4071,17 @@ mod tests {
     #[test]
     #[cfg(feature = "cluster_metadata")]
     fn test_get_cluster_metadata_without_metadata() {
-        let data = [0.0, 0.0, 0.0, 0.0, 0.0, 1000.0, 0.0];
+        let data = cluster_data(1000);
         let i = 0;
         let metadata = vec![];
 
-        let result = get_cluster_metadata(&data, i, &metadata);
+        let result = get_cluster_metadata(
+            &data,
+            i,
+            &Supercluster::builder().build().unwrap(),
+            &metadata,
+            &[],
+        );
 
         assert!(result.get("cluster").unwrap().as_bool().unwrap());
         assert_eq!(result.get("cluster_id").unwrap().as_i64().unwrap(), 0);
@@ -1181,6 +4097,137 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_parallel_clustering_matches_serial() {
+        let places: Vec<Feature> =
+            serde_json::from_str(include_str!("../tests/common/places.json")).unwrap();
+
+        // Jittered copies of the places, beyond the parallel sort and neighbor chunk sizes
+        let features: Vec<Feature> = (0..64)
+            .flat_map(|k| {
+                places.iter().cloned().map(move |mut feature| {
+                    if let Some(Geometry {
+                        value: Point(coords),
+                        ..
+                    }) = feature.geometry.as_mut()
+                    {
+                        coords[0] += k as f64 * 1e-4;
+                        coords[1] -= k as f64 * 1e-4;
+                    }
+
+                    feature
+                })
+            })
+            .collect();
+
+        let mut supercluster = setup();
+        supercluster.load(features).unwrap();
+
+        let min_zoom = supercluster.options.min_zoom as usize;
+        let max_zoom = supercluster.options.max_zoom as usize;
+
+        let mut data = supercluster.trees[&(max_zoom + 1)].data.clone();
+        data.reset();
+
+        assert!(data.len() > NEIGHBORS_CHUNK_SIZE * 2);
+
+        let mut tree = supercluster.create_tree(data);
+
+        #[cfg(feature = "cluster_metadata")]
+        let mut metadata = vec![];
+
+        for zoom in (min_zoom..=max_zoom).rev() {
+            let mut data = tree.data.clone();
+            let mut next_data = ClusterData::new(supercluster.columns);
+
+            supercluster.cluster_radius(
+                &tree,
+                &mut data,
+                &mut next_data,
+                zoom,
+                #[cfg(feature = "cluster_metadata")]
+                &mut metadata,
+            );
+
+            assert_eq!(data, supercluster.trees[&(zoom + 1)].data, "zoom {zoom}");
+
+            tree = supercluster.create_tree(next_data);
+
+            assert_eq!(tree.ids, supercluster.trees[&zoom].ids, "zoom {zoom}");
+            assert_eq!(tree.coords, supercluster.trees[&zoom].coords, "zoom {zoom}");
+        }
+
+        assert_eq!(tree.data, supercluster.trees[&min_zoom].data);
+
+        #[cfg(feature = "cluster_metadata")]
+        assert_eq!(metadata, supercluster.metadata);
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let hull = convex_hull(vec![
+            [0.0, 0.0],
+            [2.0, 2.0],
+            [1.0, 1.0],
+            [2.0, 0.0],
+            [0.0, 2.0],
+            [1.0, 0.0],
+            [0.0, 0.0],
+        ]);
+
+        assert_eq!(
+            hull,
+            Polygon(vec![vec![
+                vec![0.0, 0.0],
+                vec![2.0, 0.0],
+                vec![2.0, 2.0],
+                vec![0.0, 2.0],
+                vec![0.0, 0.0],
+            ]])
+        );
+
+        let hull = convex_hull(vec![[1.0, 1.0], [0.0, 0.0], [2.0, 2.0], [1.0, 1.0]]);
+
+        assert_eq!(hull, LineString(vec![vec![0.0, 0.0], vec![2.0, 2.0]]));
+
+        let hull = convex_hull(vec![[1.0, 1.0], [1.0, 1.0]]);
+
+        assert_eq!(hull, Point(vec![1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_get_distance() {
+        let point = |lng: f64, lat: f64| {
+            [
+                convert_longitude_to_spherical_mercator(lng),
+                convert_latitude_to_spherical_mercator(lat),
+            ]
+        };
+
+        assert_eq!(get_distance(point(10.0, 60.0), point(10.0, 60.0)), 0.0);
+        assert!((get_distance(point(0.0, 0.0), point(1.0, 0.0)) - 111319.49).abs() < 0.01);
+        assert!((get_distance(point(0.0, 60.0), point(1.0, 60.0)) - 55659.54).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_search_radius_in_meters() {
+        let options = Supercluster::builder()
+            .radius(5000.0)
+            .radius_unit(RadiusUnit::Meters)
+            .build()
+            .unwrap();
+        let y = convert_latitude_to_spherical_mercator(60.0);
+
+        // The radius does not depend on the zoom level, and doubles at 60 degrees of latitude
+        let equator = get_search_radius(&options, 0, 0.5);
+
+        assert_eq!(equator, get_search_radius(&options, 16, 0.5));
+        assert!((equator * 2.0 * PI * EARTH_RADIUS / 5000.0 - 1.0).abs() < 1e-5);
+        assert!(get_search_radius(&options, 0, y) > 2.0 * equator);
+        assert!(get_search_radius(&options, 0, y) < 2.01 * equator);
+    }
+
     #[test]
     fn test_convert_longitude_to_spherical_mercator() {
         assert_eq!(convert_longitude_to_spherical_mercator(0.0), 0.5);
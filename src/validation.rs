@@ -0,0 +1,173 @@
+//! # Validation module
+//!
+//! Contains the policy applied to the invalid input points, the reasons a point is invalid,
+//! and the report of the points skipped while loading.
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Handling of the input points without geometry or with invalid coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ValidationPolicy {
+    /// Fail with `SuperclusterError::InvalidFeature` on the first invalid point.
+    Reject,
+
+    /// Leave the invalid points out of the clustering and list them in the load report.
+    /// This is the default, so points with coordinates beyond ±90 latitude or ±180 longitude are no longer clustered.
+    #[default]
+    Skip,
+
+    /// Clamp the latitudes into [-90, 90], wrap the longitudes into [-180, 180] and clamp Cartesian coordinates
+    /// into the data range, and skip the points that cannot be repaired, e.g. with non-finite coordinates.
+    /// Without the clamp policy, a point outside an explicit Cartesian data range fails with `SuperclusterError::OutOfRange`.
+    Clamp,
+}
+
+/// Reason an input point is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidReason {
+    /// The feature has no geometry.
+    MissingGeometry,
+
+    /// The coordinates have fewer than two dimensions.
+    TooFewCoordinates,
+
+    /// The coordinates are NaN or infinite.
+    NonFiniteCoordinates,
+
+    /// The latitude is beyond ±90 degrees.
+    LatitudeOutOfRange,
+
+    /// The longitude is beyond ±180 degrees.
+    LongitudeOutOfRange,
+}
+
+impl fmt::Display for InvalidReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            InvalidReason::MissingGeometry => "the feature has no geometry",
+            InvalidReason::TooFewCoordinates => "the coordinates have fewer than two dimensions",
+            InvalidReason::NonFiniteCoordinates => "the coordinates are not finite",
+            InvalidReason::LatitudeOutOfRange => "the latitude is beyond ±90 degrees",
+            InvalidReason::LongitudeOutOfRange => "the longitude is beyond ±180 degrees",
+        };
+
+        write!(f, "{}", reason)
+    }
+}
+
+/// An input point left out of the clustering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SkippedPoint {
+    /// The index of the point in the input points.
+    pub index: usize,
+
+    /// The reason the point is invalid.
+    pub reason: InvalidReason,
+}
+
+/// Report of the input points skipped by a `load` or `apply` call, returned with the Supercluster instance.
+/// The skipped points are kept in the input points, so the indices of the other points are unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// The skipped points, in input order.
+    pub skipped: Vec<SkippedPoint>,
+}
+
+/// Validate the coordinates of an input point, repairing them with the clamp policy.
+///
+/// # Arguments
+///
+/// - `coordinates`: The input coordinates.
+/// - `lat_lng`: Whether the coordinates are longitudes and latitudes.
+/// - `policy`: The validation policy.
+///
+/// # Returns
+///
+/// The valid or repaired coordinates, otherwise the reason the coordinates are invalid.
+pub fn validate_coordinates(
+    mut coordinates: Vec<f64>,
+    lat_lng: bool,
+    policy: ValidationPolicy,
+) -> Result<Vec<f64>, InvalidReason> {
+    if coordinates.len() < 2 {
+        return Err(InvalidReason::TooFewCoordinates);
+    }
+
+    if !coordinates[0].is_finite() || !coordinates[1].is_finite() {
+        return Err(InvalidReason::NonFiniteCoordinates);
+    }
+
+    if !lat_lng {
+        return Ok(coordinates);
+    }
+
+    let [lng, lat] = [coordinates[0], coordinates[1]];
+
+    if policy == ValidationPolicy::Clamp {
+        if !(-180.0..=180.0).contains(&lng) {
+            coordinates[0] = (lng + 180.0).rem_euclid(360.0) - 180.0;
+        }
+
+        coordinates[1] = lat.clamp(-90.0, 90.0);
+    } else if !(-90.0..=90.0).contains(&lat) {
+        return Err(InvalidReason::LatitudeOutOfRange);
+    } else if !(-180.0..=180.0).contains(&lng) {
+        return Err(InvalidReason::LongitudeOutOfRange);
+    }
+
+    Ok(coordinates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_coordinates() {
+        let validate =
+            |coordinates: Vec<f64>, policy| validate_coordinates(coordinates, true, policy);
+
+        assert_eq!(
+            validate(vec![10.0, 20.0, 30.0], ValidationPolicy::Skip),
+            Ok(vec![10.0, 20.0, 30.0])
+        );
+        assert_eq!(
+            validate(vec![10.0], ValidationPolicy::Clamp),
+            Err(InvalidReason::TooFewCoordinates)
+        );
+        assert_eq!(
+            validate(vec![f64::NAN, 20.0], ValidationPolicy::Clamp),
+            Err(InvalidReason::NonFiniteCoordinates)
+        );
+        assert_eq!(
+            validate(vec![10.0, f64::INFINITY], ValidationPolicy::Reject),
+            Err(InvalidReason::NonFiniteCoordinates)
+        );
+        assert_eq!(
+            validate(vec![10.0, 95.0], ValidationPolicy::Reject),
+            Err(InvalidReason::LatitudeOutOfRange)
+        );
+        assert_eq!(
+            validate(vec![-190.0, 20.0], ValidationPolicy::Skip),
+            Err(InvalidReason::LongitudeOutOfRange)
+        );
+        assert_eq!(
+            validate(vec![190.0, -95.0], ValidationPolicy::Clamp),
+            Ok(vec![-170.0, -90.0])
+        );
+        assert_eq!(
+            validate(vec![180.0, 90.0], ValidationPolicy::Clamp),
+            Ok(vec![180.0, 90.0])
+        );
+
+        // Cartesian coordinates are not bounded to the longitudes and latitudes
+        assert_eq!(
+            validate_coordinates(vec![500.0, -500.0], false, ValidationPolicy::Reject),
+            Ok(vec![500.0, -500.0])
+        );
+    }
+}
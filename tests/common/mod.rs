@@ -1,5 +1,5 @@
 use geojson::{Feature, FeatureCollection, Value};
-use std::{fs, path::Path};
+use std::{collections::BTreeMap, fs, path::Path};
 use supercluster::range::DataRange;
 
 pub fn get_data_range(data: &Vec<Feature>) -> Option<DataRange> {
@@ -56,6 +56,13 @@ pub fn load_tile_places_with_min_5() -> FeatureCollection {
     serde_json::from_str(&json_string).expect("places-z0-0-0-min5.json was not parsed")
 }
 
+pub fn load_tile_coordinates_places() -> BTreeMap<String, Vec<Vec<f64>>> {
+    let file_path = Path::new("./tests/common/places-tiles-z0-8.json");
+    let json_string = fs::read_to_string(file_path).expect("places-tiles-z0-8.json was not found");
+
+    serde_json::from_str(&json_string).expect("places-tiles-z0-8.json was not parsed")
+}
+
 pub fn load_cartesian() -> Vec<Feature> {
     let file_path = Path::new("./tests/common/cartesian.json");
     let json_string = fs::read_to_string(file_path).expect("cartesian.json was not found");
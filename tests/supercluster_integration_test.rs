@@ -1,10 +1,25 @@
 mod common;
 
 use common::{
-    get_data_range, load_cartesian, load_places, load_tile_places, load_tile_places_with_min_5,
+    get_data_range, load_cartesian, load_places, load_tile_coordinates_places, load_tile_places,
+    load_tile_places_with_min_5,
+};
+use geojson::{
+    feature::Id,
+    Feature, Geometry, JsonObject,
+    Value::{LineString, MultiPoint, Point, Polygon},
+};
+#[cfg(feature = "reader")]
+use supercluster::GeoJsonFormat;
+use supercluster::{
+    AxisScaling, Cluster, ClusterAlgorithm, ClusterId, ClusterIdMode, ClusterItem, ClusterPoint,
+    ClusterPosition, CoordinateSystem, DataRange, GeometryAnchor, GeometryMode, GridPlacement,
+    InvalidReason, LoadReport, PlateCarree, PointChange, Projection, RadiusUnit, SkippedPoint,
+    Supercluster, SuperclusterError, ValidationPolicy, ZoomLevel,
 };
-use geojson::{Feature, Geometry, JsonObject, Value::Point};
-use supercluster::{CoordinateSystem, Supercluster, SuperclusterError};
+
+/// Tolerance of the cluster coordinates, which are stored as `f32` with the `f32` feature.
+const COORDINATE_TOLERANCE: f64 = if cfg!(feature = "f32") { 1e-4 } else { 1e-9 };
 
 #[test]
 fn test_get_tile() {
@@ -16,15 +31,51 @@ fn test_get_tile() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        // The fixture matches the JavaScript library, which does not cluster across the antimeridian
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
     let tile = index.get_tile(0, 0.0, 0.0).unwrap();
 
     assert_eq!(tile.features.len(), places_tile.features.len());
     assert_eq!(tile.features, places_tile.features);
 }
 
+#[test]
+fn test_get_tile_matches_f64_coordinates() {
+    // The fixture holds the tile coordinates computed with f64 coordinates, at every clustering zoom of the places
+    let places_tiles = load_tile_coordinates_places();
+
+    let options = Supercluster::builder()
+        .coordinate_system(CoordinateSystem::LatLng)
+        // The fixture was computed before the clustering across the antimeridian
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(load_places()).unwrap();
+
+    for (key, expected) in places_tiles {
+        let [z, x, y]: [u8; 3] = key
+            .split('/')
+            .map(|part| part.parse().unwrap())
+            .collect::<Vec<u8>>()
+            .try_into()
+            .unwrap();
+        let tile = index.get_tile(z, x as f64, y as f64).unwrap();
+        let coordinates: Vec<Vec<f64>> = tile
+            .features
+            .iter()
+            .map(|feature| match &feature.geometry.as_ref().unwrap().value {
+                Point(coordinates) => coordinates.clone(),
+                _ => vec![],
+            })
+            .collect();
+
+        assert_eq!(coordinates, expected, "tile {}", key);
+    }
+}
+
 #[test]
 fn test_get_tile_not_found() {
     let options = Supercluster::builder()
@@ -33,9 +84,10 @@ fn test_get_tile_not_found() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
 
     assert_eq!(
         index.get_tile(10, 10.0, 10.0),
@@ -53,9 +105,10 @@ fn test_generate_clusters_with_min_points() {
         .min_points(5)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
 
     let tile = index.get_tile(0, 0.0, 0.0).expect("cannot get a tile");
 
@@ -72,12 +125,13 @@ fn test_get_cluster() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
 
     let cluster_counts: Vec<usize> = index
-        .get_children(164)
+        .get_children(ClusterId(164))
         .unwrap()
         .iter()
         .map(|cluster| {
@@ -100,12 +154,13 @@ fn test_get_cluster_fail_with_undefined_tree() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
 
     assert_eq!(
-        index.get_children(100000),
+        index.get_children(ClusterId(100000)),
         Err(SuperclusterError::TreeNotFound)
     );
 }
@@ -118,15 +173,16 @@ fn test_cluster_expansion_zoom() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
 
-    assert_eq!(index.get_cluster_expansion_zoom(164), 1);
-    assert_eq!(index.get_cluster_expansion_zoom(196), 1);
-    assert_eq!(index.get_cluster_expansion_zoom(581), 2);
-    assert_eq!(index.get_cluster_expansion_zoom(1157), 2);
-    assert_eq!(index.get_cluster_expansion_zoom(4134), 3);
+    assert_eq!(index.get_cluster_expansion_zoom(ClusterId(164)), Ok(1));
+    assert_eq!(index.get_cluster_expansion_zoom(ClusterId(196)), Ok(1));
+    assert_eq!(index.get_cluster_expansion_zoom(ClusterId(581)), Ok(2));
+    assert_eq!(index.get_cluster_expansion_zoom(ClusterId(1157)), Ok(2));
+    assert_eq!(index.get_cluster_expansion_zoom(ClusterId(4134)), Ok(3));
 }
 
 #[test]
@@ -137,11 +193,12 @@ fn test_cluster_expansion_zoom_for_max_zoom() {
         .min_points(2)
         .max_zoom(4)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
 
-    assert_eq!(index.get_cluster_expansion_zoom(2504), 5);
+    assert_eq!(index.get_cluster_expansion_zoom(ClusterId(2504)), Ok(5));
 }
 
 #[test]
@@ -165,12 +222,13 @@ fn test_get_cluster_leaves() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
 
     let leaf_names: Vec<String> = index
-        .get_leaves(164, 10, 5)
+        .get_leaves(ClusterId(164), 10, 5)
         .iter()
         .map(|leaf| leaf.property("name").unwrap().as_str().unwrap().to_string())
         .collect();
@@ -187,9 +245,10 @@ fn test_clusters_when_query_crosses_international_dateline() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster
+    let (index, _) = cluster
         .load(vec![
             Feature {
                 id: None,
@@ -232,6 +291,81 @@ fn test_clusters_when_query_crosses_international_dateline() {
     assert_eq!(non_crossing.len(), crossing.len());
 }
 
+#[test]
+fn test_clusters_across_international_dateline() {
+    // Ferry terminals on both sides of the antimeridian in Fiji, about 21 km apart
+    let features = Supercluster::feature_builder()
+        .add_point(vec![179.9, -16.8])
+        .add_point(vec![-179.9, -16.8])
+        .build();
+    let bbox = [-180.0, -85.0, 180.0, 85.0];
+
+    let options = Supercluster::builder()
+        .wrap_clusters(true)
+        .cluster_bbox(true)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features.clone()).unwrap();
+
+    let items = index.get_cluster_items(bbox, 5).unwrap();
+
+    assert_eq!(items.len(), 1);
+
+    let cluster = match &items[0] {
+        ClusterItem::Cluster(cluster) => cluster,
+        ClusterItem::Point { .. } => panic!("the terminals are clustered"),
+    };
+
+    // The cluster is on the antimeridian, not halfway around the globe
+    assert_eq!(cluster.count, 2);
+    assert!((cluster.coords[0].abs() - 180.0).abs() < COORDINATE_TOLERANCE);
+    assert!((cluster.coords[1] + 16.8).abs() < COORDINATE_TOLERANCE);
+
+    // The bounding box across the antimeridian has a western bound greater than its eastern bound
+    let cluster_bbox = cluster.bbox.unwrap();
+
+    for (actual, expected) in cluster_bbox.iter().zip([179.9, -16.8, -179.9, -16.8]) {
+        assert!((actual - expected).abs() < COORDINATE_TOLERANCE);
+    }
+
+    assert_eq!(index.get_children(cluster.id).unwrap().len(), 2);
+    assert_eq!(index.get_cluster_expansion_zoom(cluster.id), Ok(8));
+    assert_eq!(index.get_cluster_items(bbox, 8).unwrap().len(), 2);
+
+    // Without wrapping, the terminals are on the opposite edges of the world
+    let options = Supercluster::builder().build().unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features.clone()).unwrap();
+
+    assert_eq!(index.get_cluster_items(bbox, 5).unwrap().len(), 2);
+
+    // Cartesian coordinates never wrap
+    let options = Supercluster::builder()
+        .coordinate_system(CoordinateSystem::Cartesian {
+            range: DataRange {
+                min_x: -180.0,
+                min_y: -90.0,
+                max_x: 180.0,
+                max_y: 90.0,
+                ..Default::default()
+            },
+        })
+        .wrap_clusters(true)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features).unwrap();
+
+    assert_eq!(
+        index
+            .get_cluster_items([-180.0, -90.0, 180.0, 90.0], 5)
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
 #[test]
 fn test_does_not_crash_on_weird_bbox_values() {
     let options = Supercluster::builder()
@@ -240,9 +374,10 @@ fn test_does_not_crash_on_weird_bbox_values() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::LatLng)
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(load_places()).unwrap();
+    let (index, _) = cluster.load(load_places()).unwrap();
 
     assert_eq!(
         index
@@ -306,9 +441,10 @@ fn test_cartesian_coordinates() {
         .min_points(2)
         .max_zoom(16)
         .coordinate_system(CoordinateSystem::Cartesian { range })
-        .build();
+        .build()
+        .unwrap();
     let mut cluster = Supercluster::new(options);
-    let index = cluster.load(data).unwrap();
+    let (index, _) = cluster.load(data).unwrap();
 
     let clusters = index.get_clusters([0.0, 0.0, 1000.0, 1000.0], 0).unwrap();
 
@@ -318,3 +454,2006 @@ fn test_cartesian_coordinates() {
     assert_eq!(clusters[2].property("point_count"), None);
     assert_eq!(clusters[3].property("point_count").unwrap(), 3);
 }
+
+#[test]
+fn test_cartesian_auto_range() {
+    let data = load_cartesian();
+    let range = DataRange {
+        auto: true,
+        ..Default::default()
+    };
+
+    let options = Supercluster::builder()
+        .radius(20.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::Cartesian { range })
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(data.clone()).unwrap();
+
+    // The fitted range is the one computed from the points beforehand
+    let expected = get_data_range(&data).unwrap();
+    let items = index
+        .get_cluster_items([0.0, 0.0, 1000.0, 1000.0], 0)
+        .unwrap();
+
+    let CoordinateSystem::Cartesian { range } = &index.options.coordinate_system else {
+        panic!("the coordinate system is not Cartesian");
+    };
+
+    assert_eq!(
+        [range.min_x, range.min_y, range.max_x, range.max_y],
+        [
+            expected.min_x,
+            expected.min_y,
+            expected.max_x,
+            expected.max_y
+        ]
+    );
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0].count(), 3);
+    assert_eq!(items[3].count(), 3);
+
+    // A filtered hierarchy keeps the range fitted to all the points
+    let median_x = (expected.min_x + expected.max_x) / 2.0;
+
+    index
+        .get_clusters_filtered(
+            "west",
+            |point| point.coords().unwrap()[0] < median_x,
+            [0.0, 0.0, 1000.0, 1000.0],
+            0,
+        )
+        .unwrap();
+
+    let filtered = index.filters.get("west").unwrap();
+    let CoordinateSystem::Cartesian { range } = &filtered.options.coordinate_system else {
+        panic!("the coordinate system is not Cartesian");
+    };
+
+    assert!(!range.auto);
+    assert_eq!(
+        [range.min_x, range.min_y, range.max_x, range.max_y],
+        [
+            expected.min_x,
+            expected.min_y,
+            expected.max_x,
+            expected.max_y
+        ]
+    );
+
+    // A point inside the fitted range keeps the range
+    let inside = Supercluster::feature_builder()
+        .add_point(vec![expected.min_x, expected.min_y])
+        .build();
+
+    index.insert(inside[0].clone()).unwrap();
+
+    let CoordinateSystem::Cartesian { range } = &index.options.coordinate_system else {
+        panic!("the coordinate system is not Cartesian");
+    };
+
+    assert_eq!(range.max_x, expected.max_x);
+
+    // A point outside the fitted range is not skipped, the range is fitted again to every point
+    let outside = Supercluster::feature_builder()
+        .add_point(vec![expected.max_x + 100.0, expected.max_y])
+        .build();
+
+    let (index, report) = index.insert(outside[0].clone()).unwrap();
+
+    let CoordinateSystem::Cartesian { range } = &index.options.coordinate_system else {
+        panic!("the coordinate system is not Cartesian");
+    };
+
+    assert!(report.skipped.is_empty());
+    assert_eq!(range.max_x, expected.max_x + 100.0);
+    assert_eq!(index.points.len(), data.len() + 2);
+
+    let points = [data.clone(), inside, outside].concat();
+    let options = Supercluster::builder()
+        .radius(20.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::Cartesian {
+            range: DataRange {
+                auto: true,
+                ..Default::default()
+            },
+        })
+        .build()
+        .unwrap();
+    let mut reloaded = Supercluster::new(options);
+    let (reloaded, _) = reloaded.load(points).unwrap();
+
+    assert_eq!(
+        index.get_clusters([0.0, 0.0, 2000.0, 2000.0], 0).unwrap(),
+        reloaded
+            .get_clusters([0.0, 0.0, 2000.0, 2000.0], 0)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_cartesian_out_of_range() {
+    let data = load_cartesian();
+
+    let load = |policy: ValidationPolicy| {
+        let options = Supercluster::builder()
+            .coordinate_system(CoordinateSystem::Cartesian {
+                range: DataRange::default(),
+            })
+            .validation(policy)
+            .build()
+            .unwrap();
+        let mut cluster = Supercluster::new(options);
+
+        cluster
+            .load(data.clone())
+            .map(|(index, report)| (report.skipped.len(), index.trees[&17].data.len()))
+    };
+
+    // The points outside the unit range are an error unless they are clamped onto its edges
+    assert_eq!(
+        load(ValidationPolicy::Reject),
+        Err(SuperclusterError::OutOfRange(0))
+    );
+    assert_eq!(
+        load(ValidationPolicy::Skip),
+        Err(SuperclusterError::OutOfRange(0))
+    );
+    assert_eq!(load(ValidationPolicy::Clamp), Ok((0, data.len())));
+
+    // A changed point outside the range is an error before any change is applied
+    let options = Supercluster::builder()
+        .coordinate_system(CoordinateSystem::Cartesian {
+            range: DataRange::default(),
+        })
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let points = Supercluster::feature_builder()
+        .add_point(vec![0.25, 0.25])
+        .add_point(vec![0.75, 0.75])
+        .add_point(vec![2.0, 0.5])
+        .build();
+    let (index, _) = cluster.load(points[..2].to_vec()).unwrap();
+
+    assert_eq!(
+        index
+            .apply(vec![
+                PointChange::Remove(0),
+                PointChange::Insert(points[2].clone())
+            ])
+            .err(),
+        Some(SuperclusterError::OutOfRange(1))
+    );
+    assert_eq!(index.points, points[..2]);
+}
+
+#[test]
+fn test_cartesian_axis_scaling() {
+    // A slide of 10000x200 units, with pairs of points 100 units apart along each axis
+    let features = Supercluster::feature_builder()
+        .add_point(vec![5000.0, 50.0])
+        .add_point(vec![5000.0, 150.0])
+        .add_point(vec![2000.0, 100.0])
+        .add_point(vec![2100.0, 100.0])
+        .build();
+    let get_clusters = |scaling: AxisScaling| {
+        let range = DataRange {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 10000.0,
+            max_y: 200.0,
+            scaling,
+            ..Default::default()
+        };
+        let options = Supercluster::builder()
+            .coordinate_system(CoordinateSystem::Cartesian { range })
+            .build()
+            .unwrap();
+        let mut cluster = Supercluster::new(options);
+
+        cluster
+            .load(features.clone())
+            .unwrap()
+            .0
+            .get_cluster_items([0.0, 0.0, 10000.0, 200.0], 2)
+            .unwrap()
+            .into_iter()
+            .filter_map(|item| match item {
+                ClusterItem::Cluster(cluster) => Some(cluster),
+                _ => None,
+            })
+            .collect::<Vec<Cluster>>()
+    };
+
+    // Both pairs are merged when the aspect ratio is preserved
+    let clusters = get_clusters(AxisScaling::PreserveAspect);
+
+    assert_eq!(clusters.len(), 2);
+    assert!(clusters.iter().any(|cluster| {
+        (cluster.coords[0] - 5000.0).abs() < COORDINATE_TOLERANCE * 1e4
+            && (cluster.coords[1] - 100.0).abs() < COORDINATE_TOLERANCE * 1e4
+    }));
+
+    // The pair along the short axis is stretched apart
+    let clusters = get_clusters(AxisScaling::Stretch);
+
+    assert_eq!(clusters.len(), 1);
+    assert!((clusters[0].coords[0] - 2050.0).abs() < COORDINATE_TOLERANCE * 1e4);
+    assert!((clusters[0].coords[1] - 100.0).abs() < COORDINATE_TOLERANCE * 1e4);
+}
+
+#[test]
+#[cfg(feature = "cluster_metadata")]
+fn test_aggregate_cluster_properties_with_reduce() {
+    let options = Supercluster::builder()
+        .radius(100.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::LatLng)
+        .map(|properties| {
+            let mut mapped = JsonObject::new();
+            mapped.insert("sum".to_string(), properties["scalerank"].clone());
+            mapped
+        })
+        .reduce(|accumulated, properties| {
+            let sum = accumulated["sum"].as_u64().unwrap() + properties["sum"].as_u64().unwrap();
+            accumulated.insert("sum".to_string(), serde_json::json!(sum));
+        })
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(load_places()).unwrap();
+
+    let sums = |z: u8| -> Vec<u64> {
+        index
+            .get_tile(z, 0.0, 0.0)
+            .unwrap()
+            .features
+            .iter()
+            .filter_map(|feature| feature.property("sum").and_then(|sum| sum.as_u64()))
+            .collect()
+    };
+
+    assert_eq!(sums(1), vec![146, 84, 63, 23, 34, 12, 19, 29, 8, 8, 80, 35]);
+    assert_eq!(
+        sums(0),
+        vec![298, 122, 12, 36, 98, 7, 24, 8, 125, 98, 125, 12, 36, 8]
+    );
+
+    let children = index.get_children(ClusterId(164)).unwrap();
+
+    assert!(children
+        .iter()
+        .filter(|child| child.contains_property("cluster"))
+        .all(|child| child.contains_property("sum")));
+}
+
+#[test]
+fn test_incremental_changes() {
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::LatLng)
+        .build()
+        .unwrap();
+    let mut places = load_places();
+    let moved = Feature {
+        geometry: Some(Geometry::new(Point(vec![-77.03, 38.91]))),
+        ..places[10].clone()
+    };
+    let inserted = Feature {
+        geometry: Some(Geometry::new(Point(vec![-77.04, 38.9]))),
+        ..places[20].clone()
+    };
+
+    let mut incremental = Supercluster::new(options.clone());
+    incremental.load(places.clone()).unwrap();
+
+    incremental.update(10, moved.clone()).unwrap();
+    incremental.remove(3).unwrap();
+    incremental.insert(inserted.clone()).unwrap();
+    incremental.insert(places[0].clone()).unwrap();
+    incremental.remove(0).unwrap();
+
+    places[10] = moved;
+    places.swap_remove(3);
+    places.push(inserted);
+    places.push(places[0].clone());
+    places.swap_remove(0);
+
+    let mut reloaded = Supercluster::new(options);
+    reloaded.load(places.clone()).unwrap();
+
+    assert_eq!(incremental.points, places);
+
+    // The clusters, including their positions, counts and IDs, are the same as after a reload
+    for zoom in 0..=17 {
+        assert_eq!(
+            incremental.get_clusters([-180.0, -85.0, 180.0, 85.0], zoom),
+            reloaded.get_clusters([-180.0, -85.0, 180.0, 85.0], zoom)
+        );
+    }
+
+    // So are the tiles of every point on each zoom level
+    for zoom in 0..=16u8 {
+        let z2 = 2f64.powi(zoom as i32);
+        let mut tiles: Vec<(u32, u32)> = places
+            .iter()
+            .filter_map(
+                |place| match place.geometry.as_ref().map(|geometry| &geometry.value) {
+                    Some(Point(coords)) => {
+                        let (lng, lat) = (coords[0], coords[1].to_radians());
+                        let x = (lng + 180.0) / 360.0 * z2;
+                        let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI)
+                            / 2.0
+                            * z2;
+
+                        Some((x.clamp(0.0, z2 - 1.0) as u32, y.clamp(0.0, z2 - 1.0) as u32))
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+
+        tiles.sort_unstable();
+        tiles.dedup();
+
+        for (x, y) in tiles {
+            assert_eq!(
+                incremental.get_tile(zoom, x as f64, y as f64),
+                reloaded.get_tile(zoom, x as f64, y as f64)
+            );
+        }
+    }
+
+    // A batch with a failing change is not applied at all
+    let clusters = incremental.get_clusters([-180.0, -85.0, 180.0, 85.0], 4);
+
+    assert_eq!(
+        incremental
+            .apply(vec![
+                PointChange::Insert(places[1].clone()),
+                PointChange::Remove(0),
+                PointChange::Update(places.len(), places[2].clone()),
+            ])
+            .err(),
+        Some(SuperclusterError::PointNotFound)
+    );
+    assert_eq!(incremental.points, places);
+    assert_eq!(
+        incremental.get_clusters([-180.0, -85.0, 180.0, 85.0], 4),
+        clusters
+    );
+}
+
+#[test]
+fn test_incremental_changes_by_id() {
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::LatLng)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let features = Supercluster::feature_builder()
+        .add_point(vec![0.0, 0.0])
+        .add_point(vec![0.0, 0.0001])
+        .build();
+    let id = features[0].id.clone().unwrap();
+    let other_id = features[1].id.clone().unwrap();
+
+    cluster.load(features).unwrap();
+
+    assert_eq!(cluster.find_point(&other_id), Some(1));
+
+    assert_eq!(
+        cluster
+            .get_clusters([-1.0, -1.0, 1.0, 1.0], 0)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    cluster
+        .update_by_id(
+            &id,
+            Feature {
+                id: Some(id.clone()),
+                geometry: Some(Geometry::new(Point(vec![90.0, 0.0]))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        cluster
+            .get_clusters([-1.0, -1.0, 1.0, 1.0], 0)
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        cluster
+            .get_clusters([89.0, -1.0, 91.0, 1.0], 0)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    cluster.remove_by_id(&id).unwrap();
+
+    assert_eq!(cluster.points.len(), 1);
+    assert_eq!(cluster.find_point(&id), None);
+    assert_eq!(cluster.find_point(&other_id), Some(0));
+    assert!(cluster
+        .get_clusters([89.0, -1.0, 91.0, 1.0], 0)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        cluster.remove_by_id(&id).err(),
+        Some(SuperclusterError::PointNotFound)
+    );
+    assert_eq!(
+        cluster.remove(5).err(),
+        Some(SuperclusterError::PointNotFound)
+    );
+
+    cluster.remove(0).unwrap();
+
+    assert!(cluster
+        .get_clusters([-180.0, -85.0, 180.0, 85.0], 0)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+#[cfg(feature = "cluster_metadata")]
+fn test_weighted_points() {
+    let features = |weights: [f64; 2]| -> Vec<Feature> {
+        vec![
+            Feature {
+                geometry: Some(Geometry::new(Point(vec![0.0, 0.0]))),
+                properties: Some(
+                    serde_json::json!({ "connectors": weights[0] })
+                        .as_object()
+                        .unwrap()
+                        .clone(),
+                ),
+                ..Default::default()
+            },
+            Feature {
+                geometry: Some(Geometry::new(Point(vec![1.0, 0.0]))),
+                properties: Some(
+                    serde_json::json!({ "connectors": weights[1] })
+                        .as_object()
+                        .unwrap()
+                        .clone(),
+                ),
+                ..Default::default()
+            },
+        ]
+    };
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(5)
+        .max_zoom(16)
+        .weight_property("connectors")
+        .build()
+        .unwrap();
+
+    let mut cluster = Supercluster::new(options.clone());
+    let (index, _) = cluster.load(features([1.0, 1.0])).unwrap();
+
+    assert_eq!(
+        index
+            .get_clusters([-180.0, -85.0, 180.0, 85.0], 0)
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features([4.0, 1.0])).unwrap();
+    let clusters = index.get_clusters([-180.0, -85.0, 180.0, 85.0], 0).unwrap();
+
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].property("point_count").unwrap(), 2);
+    assert_eq!(clusters[0].property("point_weight").unwrap(), 5.0);
+
+    let coordinates = match &clusters[0].geometry.as_ref().unwrap().value {
+        Point(coordinates) => coordinates.clone(),
+        _ => vec![],
+    };
+
+    assert!((coordinates[0] - 0.2).abs() < COORDINATE_TOLERANCE);
+}
+
+#[test]
+fn test_zoom_schedule() {
+    let features = vec![
+        Feature {
+            geometry: Some(Geometry::new(Point(vec![0.0, 0.0]))),
+            ..Default::default()
+        },
+        Feature {
+            geometry: Some(Geometry::new(Point(vec![10.0, 0.0]))),
+            ..Default::default()
+        },
+    ];
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .max_zoom(16)
+        .zoom_level(
+            0,
+            ZoomLevel {
+                radius: Some(20.0),
+                ..Default::default()
+            },
+        )
+        .zoom_level(
+            1,
+            ZoomLevel {
+                min_points: Some(3),
+                ..Default::default()
+            },
+        )
+        .zoom_level(
+            2,
+            ZoomLevel {
+                radius: Some(5.0),
+                ..Default::default()
+            },
+        )
+        .zoom_level(3, ZoomLevel::default())
+        .build()
+        .unwrap();
+
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features).unwrap();
+    let counts: Vec<usize> = (0..5)
+        .map(|zoom| {
+            index
+                .get_clusters([-180.0, -85.0, 180.0, 85.0], zoom)
+                .unwrap()
+                .len()
+        })
+        .collect();
+
+    assert_eq!(counts, vec![1, 2, 2, 2, 2]);
+
+    let items = index
+        .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 0)
+        .unwrap();
+    let cluster_id = match &items[0] {
+        ClusterItem::Cluster(cluster) => cluster.id,
+        ClusterItem::Point { .. } => panic!("the points are clustered"),
+    };
+
+    assert_eq!(index.get_children(cluster_id).unwrap().len(), 2);
+    assert_eq!(index.get_cluster_expansion_zoom(cluster_id), Ok(1));
+}
+
+#[test]
+fn test_grid_clustering() {
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .algorithm(ClusterAlgorithm::Grid {
+            placement: GridPlacement::Centroid,
+        })
+        .build()
+        .unwrap();
+    let places = load_places();
+    let num_points = places
+        .iter()
+        .filter(|place| place.geometry.is_some())
+        .count();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(places).unwrap();
+
+    for zoom in 0..=17 {
+        let items = index
+            .get_cluster_items([-180.0, -90.0, 180.0, 90.0], zoom)
+            .unwrap();
+        let mut total = 0;
+
+        for item in &items {
+            let ClusterItem::Cluster(cluster) = item else {
+                total += 1;
+                continue;
+            };
+
+            let cluster_id = cluster.id;
+            let point_count = cluster.count;
+            let children_count: usize = index
+                .get_child_items(cluster_id)
+                .unwrap()
+                .iter()
+                .map(ClusterItem::count)
+                .sum();
+
+            assert_eq!(children_count, point_count);
+            assert_eq!(
+                index.get_leaves(cluster_id, usize::MAX, 0).len(),
+                point_count
+            );
+            assert!(index.get_cluster_expansion_zoom(cluster_id).unwrap() > zoom as usize);
+
+            total += point_count;
+        }
+
+        assert_eq!(total, num_points);
+    }
+}
+
+#[test]
+fn test_grid_clustering_across_antimeridian() {
+    let options = |wrap_clusters: bool| {
+        Supercluster::builder()
+            .radius(64.0)
+            .extent(512.0)
+            .min_points(2)
+            .max_zoom(16)
+            .algorithm(ClusterAlgorithm::Grid {
+                placement: GridPlacement::Centroid,
+            })
+            .wrap_clusters(wrap_clusters)
+            .build()
+            .unwrap()
+    };
+    let features = Supercluster::feature_builder()
+        .add_point(vec![-180.0, 10.0])
+        .add_point(vec![180.0, 10.0])
+        .add_point(vec![0.0, 10.0])
+        .build();
+    let world = [-180.0, -85.0, 180.0, 85.0];
+
+    // The cells on both edges of the map are the same cell
+    let mut cluster = Supercluster::new(options(true));
+    let (index, _) = cluster.load(features[..2].to_vec()).unwrap();
+    let items = index.get_cluster_items(world, 0).unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].count(), 2);
+
+    let cluster_id = match &items[0] {
+        ClusterItem::Cluster(cluster) => cluster.id,
+        ClusterItem::Point { .. } => panic!("the points are clustered"),
+    };
+
+    assert_eq!(index.get_leaves(cluster_id, usize::MAX, 0).len(), 2);
+
+    let mut unwrapped = Supercluster::new(options(false));
+
+    assert_eq!(
+        unwrapped
+            .load(features[..2].to_vec())
+            .unwrap()
+            .0
+            .get_clusters(world, 0)
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // The cells changed on one edge are binned again with the rows on the other edge
+    let mut cluster = Supercluster::new(options(true));
+    let (index, _) = cluster.load(vec![features[0].clone()]).unwrap();
+
+    index.insert(features[2].clone()).unwrap();
+    index.insert(features[1].clone()).unwrap();
+
+    let mut expected = Supercluster::new(options(true));
+    let (expected, _) = expected
+        .load(vec![
+            features[0].clone(),
+            features[2].clone(),
+            features[1].clone(),
+        ])
+        .unwrap();
+
+    for zoom in 0..=17 {
+        let count = |index: &Supercluster| {
+            let mut counts: Vec<usize> = index
+                .get_cluster_items(world, zoom)
+                .unwrap()
+                .iter()
+                .map(ClusterItem::count)
+                .collect();
+
+            counts.sort_unstable();
+            counts
+        };
+
+        assert_eq!(count(index), count(expected));
+    }
+}
+
+#[test]
+fn test_grid_clustering_cell_center() {
+    let features = vec![
+        Feature {
+            geometry: Some(Geometry::new(Point(vec![10.0, 0.0]))),
+            ..Default::default()
+        },
+        Feature {
+            geometry: Some(Geometry::new(Point(vec![80.0, 0.0]))),
+            ..Default::default()
+        },
+    ];
+    let options = Supercluster::builder()
+        .radius(64.0)
+        .extent(256.0)
+        .max_zoom(16)
+        .algorithm(ClusterAlgorithm::Grid {
+            placement: GridPlacement::CellCenter,
+        })
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features).unwrap();
+    let items = index
+        .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 0)
+        .unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].count(), 2);
+
+    // Four cells across the world at zoom 0, the points fall into the cell south-east of the origin
+    let coordinates = match &items[0] {
+        ClusterItem::Cluster(cluster) => cluster.coords,
+        ClusterItem::Point { .. } => panic!("the points are clustered"),
+    };
+
+    assert!((coordinates[0] - 45.0).abs() < 1e-9);
+    assert!((coordinates[1] + 40.97989806962013).abs() < 1e-9);
+}
+
+#[test]
+fn test_partitioned_clustering() {
+    let features: Vec<Feature> = [(0.0, "fast"), (1.0, "slow"), (2.0, "fast"), (3.0, "slow")]
+        .iter()
+        .map(|(lng, speed)| Feature {
+            geometry: Some(Geometry::new(Point(vec![*lng, 0.0]))),
+            properties: Some(
+                serde_json::json!({ "speed": speed })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            ),
+            ..Default::default()
+        })
+        .collect();
+
+    for algorithm in [
+        ClusterAlgorithm::Radius,
+        ClusterAlgorithm::Grid {
+            placement: GridPlacement::Centroid,
+        },
+    ] {
+        let options = Supercluster::builder()
+            .radius(40.0)
+            .extent(512.0)
+            .max_zoom(16)
+            .algorithm(algorithm)
+            .partition_property("speed")
+            .build()
+            .unwrap();
+        let mut cluster = Supercluster::new(options);
+        let (index, _) = cluster.load(features.clone()).unwrap();
+        let items = index
+            .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 0)
+            .unwrap();
+
+        assert_eq!(index.partitions, vec!["fast", "slow"]);
+        assert_eq!(index.partition_indices["slow"], 1);
+        assert_eq!(items.len(), 2);
+
+        for item in items {
+            let ClusterItem::Cluster(cluster) = item else {
+                panic!("the points of each speed are clustered");
+            };
+            let speed = cluster.partition.unwrap();
+            let leaves = index.get_leaves(cluster.id, usize::MAX, 0);
+
+            assert_eq!(cluster.count, 2);
+            assert_eq!(leaves.len(), 2);
+            assert!(leaves
+                .iter()
+                .all(|leaf| leaf.property("speed").unwrap() == speed.as_str()));
+        }
+    }
+}
+
+#[test]
+fn test_cluster_position() {
+    let features: Vec<Feature> = [(0.0, 3.0), (2.0, 1.0), (3.0, 2.0)]
+        .iter()
+        .map(|(lng, connectors)| Feature {
+            geometry: Some(Geometry::new(Point(vec![*lng, 0.0]))),
+            properties: Some(
+                serde_json::json!({ "connectors": connectors })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            ),
+            ..Default::default()
+        })
+        .collect();
+
+    for (position, expected) in [
+        (ClusterPosition::WeightedMean, 4.0 / 3.0),
+        (ClusterPosition::Medoid, 2.0),
+        (ClusterPosition::Seed, 0.0),
+        (ClusterPosition::HighestWeight, 0.0),
+    ] {
+        let options = Supercluster::builder()
+            .radius(40.0)
+            .extent(512.0)
+            .max_zoom(0)
+            .weight_property("connectors")
+            .position(position)
+            .build()
+            .unwrap();
+        let mut cluster = Supercluster::new(options);
+        let (index, _) = cluster.load(features.clone()).unwrap();
+        let clusters = index.get_clusters([-180.0, -85.0, 180.0, 85.0], 0).unwrap();
+
+        assert_eq!(clusters.len(), 1);
+
+        let coordinates = match &clusters[0].geometry.as_ref().unwrap().value {
+            Point(coordinates) => coordinates.clone(),
+            _ => vec![],
+        };
+
+        assert!((coordinates[0] - expected).abs() < COORDINATE_TOLERANCE);
+        assert!(coordinates[1].abs() < COORDINATE_TOLERANCE);
+
+        let tile = index.get_tile(0, 0.0, 0.0).unwrap();
+        let tile_coordinates = match &tile.features[0].geometry.as_ref().unwrap().value {
+            Point(coordinates) => coordinates.clone(),
+            _ => vec![],
+        };
+
+        assert_eq!(
+            tile_coordinates,
+            vec![(512.0 * (expected / 360.0 + 0.5)).round(), 256.0]
+        );
+    }
+}
+
+#[test]
+fn test_filtered_queries() {
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .build()
+        .unwrap();
+    let is_cape = |feature: &Feature| {
+        feature
+            .property("featureclass")
+            .and_then(|value| value.as_str())
+            == Some("cape")
+    };
+
+    let mut cluster = Supercluster::new(options.clone());
+    let (index, _) = cluster.load(load_places()).unwrap();
+
+    let mut expected = Supercluster::new(options);
+    let (expected, _) = expected
+        .load(load_places().into_iter().filter(is_cape).collect())
+        .unwrap();
+
+    assert!(!expected.points.is_empty());
+    assert!(expected.points.len() < index.points.len());
+
+    for zoom in 0..=3 {
+        assert_eq!(
+            index
+                .get_clusters_filtered("capes", is_cape, [-180.0, -85.0, 180.0, 85.0], zoom)
+                .unwrap(),
+            expected
+                .get_clusters([-180.0, -85.0, 180.0, 85.0], zoom)
+                .unwrap()
+        );
+    }
+
+    assert_eq!(
+        index
+            .get_tile_filtered("capes", is_cape, 0, 0.0, 0.0)
+            .unwrap(),
+        expected.get_tile(0, 0.0, 0.0).unwrap()
+    );
+
+    // The clusters of the filtered hierarchy lead to the matching input points
+    let world = [-180.0, -85.0, 180.0, 85.0];
+    let cluster_id = expected
+        .get_cluster_items(world, 1)
+        .unwrap()
+        .iter()
+        .find_map(|item| match item {
+            ClusterItem::Cluster(cluster) => Some(cluster.id),
+            ClusterItem::Point { .. } => None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        index
+            .get_children_filtered("capes", is_cape, cluster_id)
+            .unwrap(),
+        expected.get_children(cluster_id).unwrap()
+    );
+    assert_eq!(
+        index
+            .get_leaves_filtered("capes", is_cape, cluster_id, usize::MAX, 0)
+            .unwrap(),
+        expected.get_leaves(cluster_id, usize::MAX, 0)
+    );
+    assert_eq!(
+        index.get_cluster_expansion_zoom_filtered("capes", is_cape, cluster_id),
+        expected.get_cluster_expansion_zoom(cluster_id)
+    );
+
+    // The cached hierarchy is reused until the filter is invalidated
+    assert_eq!(
+        index
+            .get_clusters_filtered("capes", |_| true, world, 0)
+            .unwrap(),
+        expected.get_clusters(world, 0).unwrap()
+    );
+
+    index.invalidate_filter("capes");
+
+    assert_eq!(
+        index
+            .get_clusters_filtered("capes", |_| true, world, 0)
+            .unwrap(),
+        index.get_clusters(world, 0).unwrap()
+    );
+
+    index.insert(load_places()[0].clone()).unwrap();
+
+    assert!(index.filters.is_empty());
+}
+
+#[test]
+fn test_cluster_bbox_and_hull() {
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .cluster_bbox(true)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(load_places()).unwrap();
+    let coordinates = |feature: &Feature| match &feature.geometry.as_ref().unwrap().value {
+        Point(coordinates) => coordinates.clone(),
+        _ => vec![],
+    };
+
+    for item in index
+        .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 2)
+        .unwrap()
+    {
+        let ClusterItem::Cluster(cluster) = item else {
+            assert_eq!(item.into_payload().bbox, None);
+            continue;
+        };
+
+        let bbox = cluster.bbox.unwrap();
+        let cluster_id = cluster.id;
+        let leaves: Vec<Vec<f64>> = index
+            .get_leaves(cluster_id, usize::MAX, 0)
+            .iter()
+            .map(coordinates)
+            .collect();
+
+        let min_lng = leaves.iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+        let min_lat = leaves.iter().map(|p| p[1]).fold(f64::INFINITY, f64::min);
+        let max_lng = leaves
+            .iter()
+            .map(|p| p[0])
+            .fold(f64::NEG_INFINITY, f64::max);
+        let max_lat = leaves
+            .iter()
+            .map(|p| p[1])
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        for (actual, expected) in bbox.iter().zip([min_lng, min_lat, max_lng, max_lat]) {
+            assert!((actual - expected).abs() < 1e-9);
+        }
+
+        let ring = match index.get_cluster_hull(cluster_id).unwrap().value {
+            Polygon(rings) => rings[0].clone(),
+            _ => vec![],
+        };
+
+        assert_eq!(ring.first(), ring.last());
+
+        // Every leaf is on the inner side of every edge of the counter-clockwise hull
+        for edge in ring.windows(2) {
+            for leaf in &leaves {
+                let cross = (edge[1][0] - edge[0][0]) * (leaf[1] - edge[0][1])
+                    - (edge[1][1] - edge[0][1]) * (leaf[0] - edge[0][0]);
+
+                assert!(cross >= -1e-9);
+            }
+        }
+    }
+
+    let tile = index.get_tile(0, 0.0, 0.0).unwrap();
+    let cluster_ids: Vec<Option<Id>> = index
+        .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 0)
+        .unwrap()
+        .into_iter()
+        .filter_map(|item| match item {
+            ClusterItem::Cluster(cluster) => Some(Some(cluster.feature_id)),
+            ClusterItem::Point { .. } => None,
+        })
+        .collect();
+
+    for feature in tile.features {
+        assert_eq!(cluster_ids.contains(&feature.id), feature.bbox.is_some());
+    }
+
+    assert_eq!(
+        index.get_cluster_hull(ClusterId(1_000_000)),
+        Err(SuperclusterError::ClusterNotFound)
+    );
+
+    // The hull of collinear leaves is a line string, and the hull of coinciding leaves is a point
+    let features = Supercluster::feature_builder()
+        .add_point(vec![10.0, 10.0])
+        .add_point(vec![10.001, 10.001])
+        .add_point(vec![10.002, 10.002])
+        .add_point(vec![-20.0, -20.0])
+        .add_point(vec![-20.0, -20.0])
+        .build();
+    let (index, _) = cluster.load(features).unwrap();
+    let hulls: Vec<Geometry> = index
+        .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 5)
+        .unwrap()
+        .iter()
+        .map(|item| match item {
+            ClusterItem::Cluster(cluster) => index.get_cluster_hull(cluster.id).unwrap(),
+            ClusterItem::Point { .. } => panic!("the points are clustered"),
+        })
+        .collect();
+
+    assert_eq!(hulls.len(), 2);
+    assert!(hulls.contains(&Geometry::new(LineString(vec![
+        vec![10.0, 10.0],
+        vec![10.002, 10.002]
+    ]))));
+    assert!(hulls.contains(&Geometry::new(Point(vec![-20.0, -20.0]))));
+}
+
+#[test]
+fn test_stable_cluster_ids() {
+    let group = |lng: f64, name: &str| -> Vec<Feature> {
+        (0..3)
+            .map(|i| Feature {
+                id: Some(Id::String(format!("{}-{}", name, i))),
+                geometry: Some(Geometry::new(Point(vec![lng + i as f64 * 0.01, 0.0]))),
+                ..Default::default()
+            })
+            .collect()
+    };
+    let cluster_ids = |index: &Supercluster| -> Vec<(String, ClusterId)> {
+        index
+            .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 10)
+            .unwrap()
+            .into_iter()
+            .map(|item| match item {
+                ClusterItem::Cluster(Cluster {
+                    id,
+                    feature_id: Id::String(stable_id),
+                    ..
+                }) => (stable_id, id),
+                _ => panic!("Cluster without a stable ID"),
+            })
+            .collect()
+    };
+    let leaf_ids = |index: &Supercluster, cluster_id: ClusterId| -> Vec<String> {
+        let mut ids: Vec<String> = index
+            .get_leaves(cluster_id, 10, 0)
+            .iter()
+            .map(|leaf| format!("{:?}", leaf.id))
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .max_zoom(16)
+        .id_mode(ClusterIdMode::Stable)
+        .build()
+        .unwrap();
+
+    let mut before = Supercluster::new(options.clone());
+    let (before, _) = before
+        .load([group(-60.0, "a"), group(60.0, "b")].concat())
+        .unwrap();
+
+    let mut after = Supercluster::new(options);
+    let (after, _) = after
+        .load([group(0.0, "c"), group(60.0, "b"), group(-60.0, "a")].concat())
+        .unwrap();
+
+    let before_ids = cluster_ids(before);
+    let after_ids = cluster_ids(after);
+
+    assert_eq!(before_ids.len(), 2);
+    assert_eq!(after_ids.len(), 3);
+
+    for (stable_id, cluster_id) in before_ids {
+        let after_cluster_id = after.get_cluster_id(&stable_id).unwrap();
+
+        // The internal IDs change with the input order and count, the stable IDs do not
+        assert_ne!(cluster_id, after_cluster_id);
+        assert_eq!(before.get_cluster_id(&stable_id), Ok(cluster_id));
+        assert_eq!(leaf_ids(before, cluster_id).len(), 3);
+        assert_eq!(
+            leaf_ids(before, cluster_id),
+            leaf_ids(after, after_cluster_id)
+        );
+    }
+
+    assert_eq!(
+        before.get_cluster_id("not a stable id"),
+        Err(SuperclusterError::ClusterNotFound)
+    );
+}
+
+#[test]
+fn test_cluster_ids_beyond_zoom_31() {
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .max_zoom(40)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster
+        .load(
+            Supercluster::feature_builder()
+                .add_point(vec![0.0, 0.0])
+                .add_point(vec![0.000_000_001, 0.0])
+                .build(),
+        )
+        .unwrap();
+
+    let items = index
+        .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 33)
+        .unwrap();
+    let cluster_id = match &items[0] {
+        ClusterItem::Cluster(cluster) => cluster.id,
+        ClusterItem::Point { .. } => panic!("the points are clustered"),
+    };
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(index.get_children(cluster_id).unwrap().len(), 2);
+    assert_eq!(index.get_leaves(cluster_id, 10, 0).len(), 2);
+    assert!(index.get_cluster_expansion_zoom(cluster_id).unwrap() > 33);
+    assert_eq!(cluster_id.origin(index), Ok(0));
+    assert!(cluster_id.zoom(index).unwrap() > 33);
+
+    // The tile of the cluster is addressed beyond zoom level 31 too
+    let tile = index.get_tile(33, 4294967296.0, 4294967296.0).unwrap();
+
+    assert_eq!(tile.features.len(), 1);
+    assert_eq!(
+        tile.features[0].id,
+        Some(Id::String(cluster_id.to_string()))
+    );
+    assert_eq!(
+        index.get_children(ClusterId(1)),
+        Err(SuperclusterError::InvalidClusterId)
+    );
+}
+
+#[test]
+fn test_invalid_options() {
+    assert!(matches!(
+        Supercluster::builder().min_zoom(10).max_zoom(5).build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder().max_zoom(255).build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder().radius(f64::NAN).build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder()
+            .zoom_level(
+                8,
+                ZoomLevel {
+                    radius: Some(-1.0),
+                    ..Default::default()
+                }
+            )
+            .build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder()
+            .radius(0.0)
+            .algorithm(ClusterAlgorithm::Grid {
+                placement: GridPlacement::Centroid
+            })
+            .build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder()
+            .algorithm(ClusterAlgorithm::Grid {
+                placement: GridPlacement::Centroid
+            })
+            .zoom_schedule_fn(|zoom| ZoomLevel {
+                radius: (zoom == 16).then_some(0.0),
+                ..Default::default()
+            })
+            .build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder().extent(0.0).build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder().node_size(0).build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder()
+            .radius_unit(RadiusUnit::Meters)
+            .coordinate_system(CoordinateSystem::Cartesian {
+                range: Default::default()
+            })
+            .build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        Supercluster::builder()
+            .radius_unit(RadiusUnit::Meters)
+            .algorithm(ClusterAlgorithm::Grid {
+                placement: GridPlacement::Centroid
+            })
+            .build(),
+        Err(SuperclusterError::InvalidOptions(_))
+    ));
+}
+
+#[test]
+fn test_radius_in_meters() {
+    // Pairs of points 4 km apart in Spain and Norway, and a pair 6 km apart on the equator
+    let east = |lng: f64, lat: f64, meters: f64| {
+        lng + (meters / (6378137.0 * lat.to_radians().cos())).to_degrees()
+    };
+    let features: Vec<Feature> = [
+        (-3.7, 40.4),
+        (east(-3.7, 40.4, 4000.0), 40.4),
+        (10.75, 65.0),
+        (east(10.75, 65.0, 4000.0), 65.0),
+        (20.0, 0.0),
+        (east(20.0, 0.0, 6000.0), 0.0),
+    ]
+    .iter()
+    .map(|(lng, lat)| Feature {
+        geometry: Some(Geometry::new(Point(vec![*lng, *lat]))),
+        ..Default::default()
+    })
+    .collect();
+
+    let options = Supercluster::builder()
+        .radius(5000.0)
+        .radius_unit(RadiusUnit::Meters)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features.clone()).unwrap();
+
+    // The clusters have the same ground size at every zoom level and latitude
+    for zoom in 0..=16 {
+        let items = index
+            .get_cluster_items([-180.0, -85.0, 180.0, 85.0], zoom)
+            .unwrap();
+        let mut counts: Vec<usize> = items.iter().map(|item| item.count()).collect();
+        counts.sort_unstable();
+
+        assert_eq!(counts, vec![1, 1, 2, 2], "zoom {}", zoom);
+    }
+
+    let cluster_id = index
+        .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 16)
+        .unwrap()
+        .into_iter()
+        .find_map(|item| match item {
+            ClusterItem::Cluster(cluster) => Some(cluster.id),
+            _ => None,
+        })
+        .unwrap();
+
+    assert_eq!(index.get_children(cluster_id).unwrap().len(), 2);
+    assert_eq!(index.get_cluster_expansion_zoom(cluster_id), Ok(17));
+
+    // With a radius in pixels, the ground distance merged depends on the latitude, so only the pair in Spain is merged
+    let options = Supercluster::builder().radius(4.6).build().unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features).unwrap();
+    let items = index
+        .get_cluster_items([-180.0, -85.0, 180.0, 85.0], 6)
+        .unwrap();
+    let clusters: Vec<Cluster> = items
+        .into_iter()
+        .filter_map(|item| match item {
+            ClusterItem::Cluster(cluster) => Some(cluster),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].count, 2);
+    assert!((clusters[0].coords[1] - 40.4).abs() < 0.01);
+}
+
+#[test]
+fn test_non_point_geometries() {
+    let feature = |id: &str, value: geojson::Value| {
+        let mut feature = Feature {
+            id: Some(Id::String(id.to_string())),
+            geometry: Some(Geometry::new(value)),
+            ..Default::default()
+        };
+        feature.set_property("name", id);
+        feature
+    };
+    let station = Polygon(vec![vec![
+        vec![10.0, 10.0],
+        vec![10.01, 10.0],
+        vec![10.01, 10.01],
+        vec![10.0, 10.01],
+        vec![10.0, 10.0],
+    ]]);
+    let features = vec![
+        feature("station", station.clone()),
+        feature("charger", Point(vec![10.02, 10.0])),
+        feature(
+            "site",
+            MultiPoint(vec![vec![-100.0, 40.0], vec![100.0, -40.0]]),
+        ),
+        feature(
+            "road",
+            LineString(vec![vec![50.0, -50.0], vec![50.02, -50.0]]),
+        ),
+    ];
+    let world = [-180.0, -85.0, 180.0, 85.0];
+
+    let options = Supercluster::builder().build().unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features.clone()).unwrap();
+
+    assert_eq!(index.get_clusters(world, 16).unwrap().len(), 1);
+
+    let options = Supercluster::builder()
+        .geometry_mode(GeometryMode::Anchored {
+            anchor: GeometryAnchor::LabelPoint,
+        })
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features).unwrap();
+
+    let leaves = index.get_clusters(world, 16).unwrap();
+    let geometry = |id: &str| -> Vec<geojson::Value> {
+        leaves
+            .iter()
+            .filter(|leaf| leaf.id == Some(Id::String(id.to_string())))
+            .map(|leaf| leaf.geometry.as_ref().unwrap().value.clone())
+            .collect()
+    };
+
+    assert_eq!(leaves.len(), 5);
+    assert_eq!(geometry("station"), vec![station.clone()]);
+    assert_eq!(geometry("road").len(), 1);
+    assert_eq!(geometry("site").len(), 2);
+    assert!(geometry("site").contains(&Point(vec![100.0, -40.0])));
+
+    // The station and the charger merge, while the points of the site stay apart
+    let items = index.get_cluster_items(world, 0).unwrap();
+    let cluster_id = items
+        .iter()
+        .find_map(|item| match item {
+            ClusterItem::Cluster(cluster) => Some(cluster.id),
+            ClusterItem::Point { .. } => None,
+        })
+        .unwrap();
+    let leaves = index.get_leaves(cluster_id, 10, 0);
+
+    assert_eq!(items.len(), 4);
+    assert_eq!(leaves.len(), 2);
+    assert!(leaves
+        .iter()
+        .any(|leaf| leaf.geometry == Some(Geometry::new(station.clone()))));
+
+    match index.get_cluster_hull(cluster_id).unwrap().value {
+        Polygon(rings) => assert_eq!(rings[0].len(), 5),
+        _ => panic!("Expected a polygon"),
+    }
+
+    // Unclustered polygons keep their geometry in tile coordinates
+    let tile = index.get_tile(16, 34589.0, 30937.0).unwrap();
+    let station = tile
+        .features
+        .iter()
+        .find(|feature| feature.id == Some(Id::String("station".to_string())))
+        .unwrap();
+
+    match &station.geometry.as_ref().unwrap().value {
+        Polygon(rings) => {
+            assert_eq!(rings[0].len(), 5);
+            assert_eq!(rings[0][0], rings[0][4]);
+            assert!(rings[0][1][0] > rings[0][0][0]);
+            assert!(rings[0][2][1] < rings[0][1][1]);
+        }
+        _ => panic!("Expected a polygon"),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Charger {
+    id: u32,
+    lng: f64,
+    lat: f64,
+    power: f64,
+    operator: &'static str,
+}
+
+#[derive(Debug, PartialEq)]
+enum ChargerItem {
+    Charger(u32),
+    Cluster(Cluster),
+}
+
+impl ClusterPoint for Charger {
+    type Payload = ChargerItem;
+
+    fn coords(&self) -> Option<[f64; 2]> {
+        Some([self.lng, self.lat])
+    }
+
+    fn payload(&self) -> ChargerItem {
+        ChargerItem::Charger(self.id)
+    }
+
+    fn cluster_payload(cluster: Cluster) -> ChargerItem {
+        ChargerItem::Cluster(cluster)
+    }
+
+    fn weight(&self) -> f64 {
+        self.power
+    }
+
+    fn partition(&self) -> Option<String> {
+        Some(self.operator.to_string())
+    }
+}
+
+#[test]
+fn test_custom_cluster_points() {
+    let chargers = vec![
+        Charger {
+            id: 1,
+            lng: 4.89,
+            lat: 52.37,
+            power: 50.0,
+            operator: "a",
+        },
+        Charger {
+            id: 2,
+            lng: 4.90,
+            lat: 52.37,
+            power: 150.0,
+            operator: "a",
+        },
+        Charger {
+            id: 3,
+            lng: 4.90,
+            lat: 52.38,
+            power: 22.0,
+            operator: "b",
+        },
+    ];
+
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::LatLng)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(chargers).unwrap();
+    let items = index.get_clusters([-180.0, -90.0, 180.0, 90.0], 0).unwrap();
+
+    // The chargers of different operators are never clustered together
+    assert_eq!(items.len(), 2);
+    assert!(items.contains(&ChargerItem::Charger(3)));
+
+    let cluster = items
+        .iter()
+        .find_map(|item| match item {
+            ChargerItem::Cluster(cluster) => Some(cluster.to_owned()),
+            ChargerItem::Charger(_) => None,
+        })
+        .unwrap();
+
+    assert_eq!(cluster.count, 2);
+    assert_eq!(cluster.weight, 200.0);
+    assert_eq!(cluster.partition, Some("a".to_string()));
+
+    let mut leaves = index.get_leaves(cluster.id, usize::MAX, 0);
+    leaves.sort_by_key(|item| match item {
+        ChargerItem::Charger(id) => *id,
+        ChargerItem::Cluster(_) => 0,
+    });
+
+    assert_eq!(
+        leaves,
+        vec![ChargerItem::Charger(1), ChargerItem::Charger(2)]
+    );
+    assert_eq!(index.get_cluster_expansion_zoom(cluster.id).unwrap(), 12);
+}
+
+#[test]
+fn test_cluster_items() {
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::LatLng)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let places = load_places();
+    let (index, _) = cluster.load(places.clone()).unwrap();
+
+    let bbox = [-180.0, -85.0, 180.0, 85.0];
+    let items = index.get_cluster_items(bbox, 2).unwrap();
+    let features: Vec<Feature> = items.iter().cloned().map(Feature::from).collect();
+
+    assert_eq!(features, index.get_clusters(bbox, 2).unwrap());
+    assert_eq!(
+        items.iter().map(|item| item.count()).sum::<usize>(),
+        places
+            .iter()
+            .filter(|place| place.coords().is_some())
+            .count()
+    );
+
+    let children = index.get_child_items(ClusterId(164)).unwrap();
+
+    assert_eq!(children.len(), 4);
+    assert_eq!(
+        children.iter().map(|item| item.count()).collect::<Vec<_>>(),
+        vec![6, 7, 2, 1]
+    );
+    assert!(matches!(
+        &children[3],
+        ClusterItem::Point { index, part: None, point } if point == &places[*index]
+    ));
+
+    let leaves = index.get_leaf_items(ClusterId(164), 10, 5);
+
+    assert_eq!(leaves.len(), 10);
+
+    for leaf in leaves {
+        match leaf {
+            ClusterItem::Point { index, point, .. } => assert_eq!(point, places[index]),
+            ClusterItem::Cluster(_) => panic!("leaves are never clusters"),
+        }
+    }
+}
+
+#[test]
+fn test_cluster_items_ref() {
+    let options = Supercluster::builder()
+        .radius(40.0)
+        .extent(512.0)
+        .min_points(2)
+        .max_zoom(16)
+        .coordinate_system(CoordinateSystem::LatLng)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(load_places()).unwrap();
+
+    let bbox = [-180.0, -85.0, 180.0, 85.0];
+    let items = index.get_cluster_items_ref(bbox, 2).unwrap();
+
+    // The borrowed points are the loaded points, not clones of them
+    for item in &items {
+        if let ClusterItem::Point {
+            index: i, point, ..
+        } = item
+        {
+            assert!(std::ptr::eq(*point, &index.points[*i]));
+        }
+    }
+
+    let features: Vec<Feature> = items.into_iter().map(Feature::from).collect();
+
+    assert_eq!(features, index.get_clusters(bbox, 2).unwrap());
+    assert_eq!(
+        index
+            .get_child_items_ref(ClusterId(164))
+            .unwrap()
+            .into_iter()
+            .map(ClusterItem::cloned)
+            .collect::<Vec<_>>(),
+        index.get_child_items(ClusterId(164)).unwrap()
+    );
+    assert_eq!(
+        index
+            .get_leaf_items_ref(ClusterId(164), 10, 5)
+            .into_iter()
+            .map(Feature::from)
+            .collect::<Vec<_>>(),
+        index.get_leaves(ClusterId(164), 10, 5)
+    );
+}
+
+#[test]
+fn test_load_iter() {
+    let options = Supercluster::builder()
+        .coordinate_system(CoordinateSystem::LatLng)
+        .build()
+        .unwrap();
+    let bbox = [-180.0, -85.0, 180.0, 85.0];
+
+    let mut cluster = Supercluster::new(options.clone());
+    let (index, _) = cluster.load(load_places()).unwrap();
+    let expected = index.get_clusters(bbox, 2).unwrap();
+
+    // Keep the points of the northern hemisphere only, without collecting them
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster
+        .load_iter(
+            load_places()
+                .into_iter()
+                .filter(|place| place.coords().is_some_and(|coords| coords[1] >= 0.0)),
+        )
+        .unwrap();
+    let clusters = index.get_clusters(bbox, 2).unwrap();
+
+    assert!(!clusters.is_empty());
+    assert!(clusters.len() < expected.len());
+    assert!(index
+        .points
+        .iter()
+        .all(|place| place.coords().unwrap()[1] >= 0.0));
+
+    let (index, _) = cluster.load_iter(load_places()).unwrap();
+
+    assert_eq!(index.get_clusters(bbox, 2).unwrap(), expected);
+}
+
+#[test]
+#[cfg(feature = "reader")]
+fn test_load_reader() {
+    let options = Supercluster::builder()
+        .coordinate_system(CoordinateSystem::LatLng)
+        .build()
+        .unwrap();
+    let bbox = [-180.0, -85.0, 180.0, 85.0];
+    let places = load_places();
+
+    let mut cluster = Supercluster::new(options.clone());
+    let expected = cluster
+        .load(places.clone())
+        .unwrap()
+        .0
+        .get_clusters(bbox, 3);
+
+    let collection = geojson::FeatureCollection {
+        bbox: None,
+        features: places.clone(),
+        foreign_members: None,
+    }
+    .to_string();
+    let mut cluster: Supercluster = Supercluster::new(options.clone());
+    let (index, report) = cluster
+        .load_reader(
+            collection.as_bytes(),
+            GeoJsonFormat::FeatureCollection,
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(index.points, places);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(index.get_clusters(bbox, 3), expected);
+
+    let lines: Vec<String> = places.iter().map(|place| place.to_string()).collect();
+    let mut cluster: Supercluster = Supercluster::new(options);
+    let (index, _) = cluster
+        .load_reader(
+            lines.join("\n").as_bytes(),
+            GeoJsonFormat::NewlineDelimited,
+            Some(&["name"]),
+        )
+        .unwrap();
+
+    assert_eq!(index.points.len(), places.len());
+    assert!(index.points.iter().all(|place| place
+        .properties
+        .as_ref()
+        .unwrap()
+        .keys()
+        .all(|key| key == "name")));
+    assert_eq!(index.points[0].property("name"), places[0].property("name"));
+
+    let result = cluster.load_reader(
+        "{\"type\": \"FeatureCollection\", \"features\": [{]}".as_bytes(),
+        GeoJsonFormat::FeatureCollection,
+        None,
+    );
+
+    assert!(matches!(result, Err(SuperclusterError::Parse(_))));
+    assert!(cluster.points.is_empty());
+    assert_eq!(
+        cluster.get_clusters(bbox, 3),
+        Err(SuperclusterError::TreeNotFound)
+    );
+
+    // The feature without geometry is rejected while the input is read
+    let options = Supercluster::builder()
+        .coordinate_system(CoordinateSystem::LatLng)
+        .validation(ValidationPolicy::Reject)
+        .build()
+        .unwrap();
+    let mut cluster: Supercluster = Supercluster::new(options);
+    let result = cluster.load_reader(
+        lines[160..].join("\n").as_bytes(),
+        GeoJsonFormat::NewlineDelimited,
+        None,
+    );
+
+    assert!(matches!(
+        result,
+        Err(SuperclusterError::InvalidFeature { index: 2, .. })
+    ));
+    assert!(cluster.points.is_empty());
+}
+
+/// North polar stereographic projection of longitudes and latitudes, with the equator on a circle of radius 0.25.
+struct NorthPolarStereographic;
+
+impl Projection for NorthPolarStereographic {
+    fn forward(&self, coords: [f64; 2]) -> [f64; 2] {
+        let r = 0.25 * ((90.0 - coords[1]) / 2.0).to_radians().tan();
+        let lng = coords[0].to_radians();
+
+        [0.5 + r * lng.sin(), 0.5 + r * lng.cos()]
+    }
+
+    fn inverse(&self, point: [f64; 2]) -> [f64; 2] {
+        let (dx, dy) = (point[0] - 0.5, point[1] - 0.5);
+        let r = dx.hypot(dy) / 0.25;
+
+        [
+            dx.atan2(dy).to_degrees(),
+            90.0 - 2.0 * r.atan().to_degrees(),
+        ]
+    }
+}
+
+#[test]
+fn test_plate_carree_projection() {
+    let features = load_places();
+    let total = features
+        .iter()
+        .filter(|feature| feature.geometry.is_some())
+        .count();
+
+    let options = Supercluster::builder()
+        .projection(PlateCarree)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features).unwrap();
+
+    let items = index
+        .get_cluster_items([-180.0, -90.0, 180.0, 90.0], 0)
+        .unwrap();
+
+    assert_eq!(items.iter().map(|item| item.count()).sum::<usize>(), total);
+
+    // The query across the antimeridian is split at the edges of the longitudes
+    let items = index
+        .get_cluster_items([170.0, -90.0, 190.0, 90.0], 16)
+        .unwrap();
+
+    assert!(!items.is_empty());
+    assert!(items.iter().all(|item| {
+        let lng = match item {
+            ClusterItem::Cluster(cluster) => cluster.coords[0],
+            ClusterItem::Point { point, .. } => point.coords().unwrap()[0],
+        };
+
+        lng >= 170.0 || lng <= -170.0
+    }));
+}
+
+#[test]
+fn test_custom_projection() {
+    let features = Supercluster::feature_builder()
+        .add_point(vec![0.0, 89.9])
+        .add_point(vec![90.0, 89.9])
+        .add_point(vec![180.0, 89.9])
+        .add_point(vec![-90.0, 89.9])
+        .add_point(vec![0.0, 60.0])
+        .build();
+
+    let options = Supercluster::builder()
+        .projection(NorthPolarStereographic)
+        .build()
+        .unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, _) = cluster.load(features).unwrap();
+
+    // The points around the pole are merged across the meridians
+    let items = index
+        .get_cluster_items([-180.0, 0.0, 180.0, 90.0], 5)
+        .unwrap();
+    let clusters: Vec<Cluster> = items
+        .into_iter()
+        .filter_map(|item| match item {
+            ClusterItem::Cluster(cluster) => Some(cluster),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0].count, 4);
+    assert!(clusters[0].coords[1] > 89.9);
+
+    let leaves = index.get_leaf_items(clusters[0].id, 10, 0);
+
+    assert_eq!(leaves.len(), 4);
+
+    // The query keeps the points within its bounds only
+    let items = index
+        .get_cluster_items([-45.0, 50.0, 45.0, 90.0], 16)
+        .unwrap();
+
+    assert_eq!(items.len(), 2);
+
+    // The tiles do not wrap around
+    let tile = index.get_tile(5, 16.0, 16.0).unwrap();
+
+    assert_eq!(tile.features.len(), 1);
+    assert!(matches!(
+        index.get_tile(5, 0.0, 16.0),
+        Err(SuperclusterError::TileNotFound)
+    ));
+}
+
+#[test]
+fn test_validation_policy() {
+    // The feature builder does not keep the order of the points, so the features are listed explicitly
+    let mut features: Vec<Feature> = [
+        vec![10.0, 20.0],
+        vec![f64::NAN, 20.0],
+        vec![10.0, 95.0],
+        vec![10.0],
+        vec![190.0, 20.0],
+    ]
+    .into_iter()
+    .map(|coordinates| Feature {
+        geometry: Some(Geometry::new(Point(coordinates))),
+        ..Default::default()
+    })
+    .collect();
+
+    features.push(Feature::default());
+
+    let load = |policy: ValidationPolicy| -> Result<(usize, LoadReport), SuperclusterError> {
+        let options = Supercluster::builder().validation(policy).build().unwrap();
+        let mut cluster = Supercluster::new(options);
+        let (index, report) = cluster.load(features.clone())?;
+        let count = index
+            .get_cluster_items([-180.0, -90.0, 180.0, 90.0], 16)
+            .unwrap()
+            .len();
+
+        Ok((count, report))
+    };
+    let skipped = |reasons: Vec<(usize, InvalidReason)>| {
+        reasons
+            .into_iter()
+            .map(|(index, reason)| SkippedPoint { index, reason })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        load(ValidationPolicy::Reject),
+        Err(SuperclusterError::InvalidFeature {
+            index: 1,
+            reason: InvalidReason::NonFiniteCoordinates
+        })
+    );
+
+    let (count, report) = load(ValidationPolicy::Skip).unwrap();
+
+    assert_eq!(count, 1);
+    assert_eq!(
+        report.skipped,
+        skipped(vec![
+            (1, InvalidReason::NonFiniteCoordinates),
+            (2, InvalidReason::LatitudeOutOfRange),
+            (3, InvalidReason::TooFewCoordinates),
+            (4, InvalidReason::LongitudeOutOfRange),
+            (5, InvalidReason::MissingGeometry),
+        ])
+    );
+
+    // The latitude is clamped to the pole and the longitude is wrapped, the others cannot be repaired
+    let (count, report) = load(ValidationPolicy::Clamp).unwrap();
+
+    assert_eq!(count, 3);
+    assert_eq!(
+        report.skipped,
+        skipped(vec![
+            (1, InvalidReason::NonFiniteCoordinates),
+            (3, InvalidReason::TooFewCoordinates),
+            (5, InvalidReason::MissingGeometry),
+        ])
+    );
+
+    // The places include a feature without geometry
+    let options = Supercluster::builder().build().unwrap();
+    let mut cluster = Supercluster::new(options);
+    let (index, report) = cluster.load(load_places()).unwrap();
+
+    assert_eq!(
+        report.skipped,
+        skipped(vec![(162, InvalidReason::MissingGeometry)])
+    );
+
+    // The report covers the changed points only
+    let (_, report) = index.insert(features[1].clone()).unwrap();
+
+    assert_eq!(
+        report.skipped,
+        skipped(vec![(163, InvalidReason::NonFiniteCoordinates)])
+    );
+}